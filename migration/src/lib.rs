@@ -6,6 +6,19 @@ mod m20251028_074726_add_room_count_column_at_floor_structure;
 mod m20251101_000001_remove_bounding_box_aspect_ri;
 mod m20251102_000001_rename_bounding_box_height_to_depth;
 mod m20251102_000002_rename_structure_tables;
+mod m20251104_000001_add_room_shape_columns;
+mod m20251105_000001_add_floor_graph_signature_column;
+mod m20251106_000001_add_room_shape_descriptor_column;
+mod m20251107_000001_add_structure_embeddings;
+mod m20251108_000001_create_index_watermarks_table;
+mod m20251109_000001_create_change_stream_tokens_table;
+mod m20251110_000001_create_jobs_table;
+mod m20251111_000001_create_structure_index_errors_table;
+mod m20251112_000001_add_job_leases;
+mod m20251112_000002_normalize_structure_embeddings;
+mod m20251113_000001_normalize_bounding_box_aspect;
+mod m20251113_000002_backfill_structure_embeddings;
+mod m20251114_000001_add_index_watermark_holds;
 
 pub struct Migrator;
 
@@ -19,6 +32,19 @@ impl MigratorTrait for Migrator {
             Box::new(m20251101_000001_remove_bounding_box_aspect_ri::Migration),
             Box::new(m20251102_000001_rename_bounding_box_height_to_depth::Migration),
             Box::new(m20251102_000002_rename_structure_tables::Migration),
+            Box::new(m20251104_000001_add_room_shape_columns::Migration),
+            Box::new(m20251105_000001_add_floor_graph_signature_column::Migration),
+            Box::new(m20251106_000001_add_room_shape_descriptor_column::Migration),
+            Box::new(m20251107_000001_add_structure_embeddings::Migration),
+            Box::new(m20251108_000001_create_index_watermarks_table::Migration),
+            Box::new(m20251109_000001_create_change_stream_tokens_table::Migration),
+            Box::new(m20251110_000001_create_jobs_table::Migration),
+            Box::new(m20251111_000001_create_structure_index_errors_table::Migration),
+            Box::new(m20251112_000001_add_job_leases::Migration),
+            Box::new(m20251112_000002_normalize_structure_embeddings::Migration),
+            Box::new(m20251113_000001_normalize_bounding_box_aspect::Migration),
+            Box::new(m20251113_000002_backfill_structure_embeddings::Migration),
+            Box::new(m20251114_000001_add_index_watermark_holds::Migration),
         ]
     }
 }
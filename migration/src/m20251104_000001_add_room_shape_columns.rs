@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rooms::Table)
+                    .add_column(ColumnDef::new(Rooms::PolygonArea).double().null())
+                    .add_column(ColumnDef::new(Rooms::Perimeter).double().null())
+                    .add_column(ColumnDef::new(Rooms::Convexity).double().null())
+                    .add_column(ColumnDef::new(Rooms::Compactness).double().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Rooms::Table)
+                    .drop_column(Rooms::PolygonArea)
+                    .drop_column(Rooms::Perimeter)
+                    .drop_column(Rooms::Convexity)
+                    .drop_column(Rooms::Compactness)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Rooms {
+    Table,
+    PolygonArea,
+    Perimeter,
+    Convexity,
+    Compactness,
+}
@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+const FLOOR_EMBEDDING_DIM: usize = 4;
+const ROOM_EMBEDDING_DIM: usize = 21;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS vector")
+            .await?;
+        db.execute_unprepared(&format!(
+            "ALTER TABLE floors ADD COLUMN IF NOT EXISTS embedding vector({FLOOR_EMBEDDING_DIM})"
+        ))
+        .await?;
+        db.execute_unprepared(&format!(
+            "ALTER TABLE rooms ADD COLUMN IF NOT EXISTS embedding vector({ROOM_EMBEDDING_DIM})"
+        ))
+        .await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_floors_embedding_hnsw \
+             ON floors USING hnsw (embedding vector_l2_ops)",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_rooms_embedding_hnsw \
+             ON rooms USING hnsw (embedding vector_l2_ops)",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_rooms_embedding_hnsw")
+            .await?;
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_floors_embedding_hnsw")
+            .await?;
+        db.execute_unprepared("ALTER TABLE rooms DROP COLUMN IF EXISTS embedding")
+            .await?;
+        db.execute_unprepared("ALTER TABLE floors DROP COLUMN IF EXISTS embedding")
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StructureIndexErrors::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(StructureIndexErrors::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(StructureIndexErrors::ProjectId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(StructureIndexErrors::JobId).uuid().null())
+                    .col(
+                        ColumnDef::new(StructureIndexErrors::Reason)
+                            .string_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StructureIndexErrors::Message)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(StructureIndexErrors::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_structure_index_errors_project_id")
+                    .table(StructureIndexErrors::Table)
+                    .col(StructureIndexErrors::ProjectId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_structure_index_errors_reason")
+                    .table(StructureIndexErrors::Table)
+                    .col(StructureIndexErrors::Reason)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StructureIndexErrors::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum StructureIndexErrors {
+    Table,
+    Id,
+    ProjectId,
+    JobId,
+    Reason,
+    Message,
+    CreatedAt,
+}
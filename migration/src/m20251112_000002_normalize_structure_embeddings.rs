@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+/// Rescale the log-scaled area and room count components of stored embeddings into `[0, 1]`,
+/// matching `scoring::embedding`; the other components are unchanged.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "UPDATE floors SET embedding = ( \
+                 ARRAY[LEAST(ln(1 + GREATEST(area, 0)) / ln(1 + 2000.0), 1.0)::real] \
+                 || (embedding::real[])[2:3] \
+                 || ARRAY[LEAST(ln(1 + GREATEST(room_count, 0)) / ln(1 + 50.0), 1.0)::real] \
+             )::vector \
+             WHERE embedding IS NOT NULL",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE rooms SET embedding = ( \
+                 ARRAY[LEAST( \
+                     ln(1 + GREATEST(COALESCE(polygon_area, area), 0) / 1000000.0) \
+                         / ln(1 + 200.0), \
+                     1.0 \
+                 )::real] \
+                 || (embedding::real[])[2:] \
+             )::vector \
+             WHERE embedding IS NOT NULL",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "UPDATE floors SET embedding = ( \
+                 ARRAY[ln(1 + GREATEST(area, 0))::real] \
+                 || (embedding::real[])[2:3] \
+                 || ARRAY[ln(1 + GREATEST(room_count, 0))::real] \
+             )::vector \
+             WHERE embedding IS NOT NULL",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE rooms SET embedding = ( \
+                 ARRAY[ln(1 + GREATEST(area, 0))::real] || (embedding::real[])[2:] \
+             )::vector \
+             WHERE embedding IS NOT NULL",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
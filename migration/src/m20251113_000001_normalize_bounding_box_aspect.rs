@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+/// Bring rows indexed before oriented bounding boxes onto the `aspect >= 1` scale: the
+/// longer side becomes the width and the inverse-aspect embedding component follows.
+///
+/// The box area and rectangularity of rotated shapes can only be recomputed from geometry,
+/// so they refresh when those projects are next indexed.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for table in ["floors", "rooms"] {
+            db.execute_unprepared(&format!(
+                "UPDATE {table} SET \
+                     bounding_box_width = bounding_box_depth, \
+                     bounding_box_depth = bounding_box_width, \
+                     bounding_box_aspect = 1 / bounding_box_aspect, \
+                     embedding = CASE WHEN embedding IS NOT NULL THEN ( \
+                         (embedding::real[])[1:1] \
+                         || ARRAY[bounding_box_aspect::real] \
+                         || (embedding::real[])[3:] \
+                     )::vector END \
+                 WHERE bounding_box_aspect > 0 AND bounding_box_aspect < 1"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Which rows were flipped is not recorded, and `aspect >= 1` rows are valid either way.
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+const DESCRIPTOR_LEN: usize = 16;
+
+/// Compute the embedding of rows indexed before embeddings were stored, so the ANN
+/// candidate search does not skip them. Mirrors `scoring::embedding`, including replacing
+/// non-finite components with `0`.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(&format!(
+            "UPDATE floors SET embedding = {}::vector WHERE embedding IS NULL",
+            finite(
+                "ARRAY[ \
+                     LEAST(ln(1 + GREATEST(area, 0)) / ln(1 + 2000.0), 1.0), \
+                     CASE WHEN bounding_box_aspect > 0 \
+                         THEN LEAST(1 / bounding_box_aspect, 1.0) ELSE 0 END, \
+                     rectangularity, \
+                     LEAST(ln(1 + GREATEST(room_count, 0)) / ln(1 + 50.0), 1.0) \
+                 ]::float8[]"
+            )
+        ))
+        .await?;
+        db.execute_unprepared(&format!(
+            "UPDATE rooms SET embedding = {}::vector WHERE embedding IS NULL",
+            finite(&format!(
+                "ARRAY[ \
+                     LEAST( \
+                         ln(1 + GREATEST(COALESCE(polygon_area, area), 0) / 1000000.0) \
+                             / ln(1 + 200.0), \
+                         1.0 \
+                     ), \
+                     CASE WHEN bounding_box_aspect > 0 \
+                         THEN LEAST(1 / bounding_box_aspect, 1.0) ELSE 0 END, \
+                     rectangularity, \
+                     COALESCE(compactness, 0), \
+                     COALESCE(convexity, 0) \
+                 ]::float8[] \
+                 || ARRAY( \
+                     SELECT COALESCE(shape_descriptor[i], 0) \
+                     FROM generate_series(1, {DESCRIPTOR_LEN}) AS i ORDER BY i \
+                 )"
+            ))
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Backfilled embeddings are indistinguishable from indexed ones and equally valid.
+        Ok(())
+    }
+}
+
+/// `components` as a `real[]` with NaN and infinities replaced by `0`, which pgvector rejects.
+fn finite(components: &str) -> String {
+    format!(
+        "ARRAY( \
+             SELECT CASE WHEN value IN ('NaN', 'Infinity', '-Infinity') THEN 0 ELSE value END \
+             FROM unnest({components}) WITH ORDINALITY AS component(value, position) \
+             ORDER BY position \
+         )::real[]"
+    )
+}
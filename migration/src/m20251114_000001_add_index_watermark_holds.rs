@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A watermark held before the very first project has no position yet.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexWatermarks::Table)
+                    .modify_column(
+                        ColumnDef::new(IndexWatermarks::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .modify_column(ColumnDef::new(IndexWatermarks::ProjectId).string().null())
+                    .add_column(
+                        ColumnDef::new(IndexWatermarks::HeldProjectId)
+                            .string()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(IndexWatermarks::HeldAttempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM index_watermarks WHERE updated_at IS NULL OR project_id IS NULL",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IndexWatermarks::Table)
+                    .drop_column(IndexWatermarks::HeldProjectId)
+                    .drop_column(IndexWatermarks::HeldAttempts)
+                    .modify_column(
+                        ColumnDef::new(IndexWatermarks::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .modify_column(
+                        ColumnDef::new(IndexWatermarks::ProjectId)
+                            .string()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IndexWatermarks {
+    Table,
+    UpdatedAt,
+    ProjectId,
+    HeldProjectId,
+    HeldAttempts,
+}
@@ -1,4 +1,11 @@
-use anyhow::Result;
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use anyhow::{Result, bail};
+
+use crate::{
+    repositories::{dependency_probe::DEFAULT_PROBE_TIMEOUT, floorplan_fetcher::FetchOptions},
+    scoring::ScoringProfiles,
+};
 
 pub struct AppConfig {
     pub port: u16,
@@ -6,8 +13,31 @@ pub struct AppConfig {
     pub mongodb_db: String,
     pub database_url: Option<String>,
     pub aws_region: Option<String>,
-    pub s3_bucket_name: Option<String>,
+    pub object_store: ObjectStoreConfig,
+    /// Store listed for `projects/{id}/images` when picking cover images, if not
+    /// `object_store`.
+    pub image_store: Option<ObjectStoreConfig>,
     pub cdn_url: String,
+    /// Whether `CDN_URL` was set rather than left at its default.
+    pub cdn_url_set: bool,
+    pub scoring_profiles: ScoringProfiles,
+    pub ann_candidates: Option<u64>,
+    pub structure_index_snapshot: Option<PathBuf>,
+    pub project_change_stream: bool,
+    pub upsert_chunk_size: Option<usize>,
+    pub index_concurrency: usize,
+    pub floorplan_fetch: FetchOptions,
+    /// Per-dependency timeout of the readiness probe.
+    pub readiness_timeout: Duration,
+}
+
+/// Backend behind `ObjectStore`, chosen with `OBJECT_STORE` (`s3`, `http` or `local`).
+/// Without it, floorplans are read from the CDN, even when `S3_BUCKET_NAME` is set for
+/// cover images.
+pub enum ObjectStoreConfig {
+    S3 { bucket: String },
+    Http,
+    Local { root: PathBuf },
 }
 
 impl AppConfig {
@@ -21,9 +51,38 @@ impl AppConfig {
             std::env::var("MONGODB_DB").unwrap_or_else(|_| "ArchisketchDB".to_string());
         let database_url = std::env::var("DATABASE_URL").ok();
         let aws_region = std::env::var("AWS_REGION").ok();
-        let s3_bucket_name = std::env::var("S3_BUCKET_NAME").ok();
+        let object_store = load_object_store()?;
+        let image_store = load_image_store(&object_store);
+        let cdn_url_set = std::env::var("CDN_URL").is_ok();
         let cdn_url = std::env::var("CDN_URL")
             .unwrap_or_else(|_| "https://dev-resources.archisketch.com".to_string());
+        let scoring_profiles = ScoringProfiles::load(
+            std::env::var("SCORING_PROFILES_PATH").ok().as_deref(),
+            std::env::var("SCORING_PROFILE").ok().as_deref(),
+        )?;
+        let ann_candidates = std::env::var("ANN_CANDIDATES")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok());
+        let structure_index_snapshot = std::env::var("STRUCTURE_INDEX_SNAPSHOT")
+            .ok()
+            .map(PathBuf::from);
+        let project_change_stream = std::env::var("PROJECT_CHANGE_STREAM")
+            .ok()
+            .is_some_and(|value| matches!(value.as_str(), "1" | "true"));
+        let upsert_chunk_size = std::env::var("STRUCTURE_UPSERT_CHUNK_SIZE")
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok());
+        let index_concurrency = std::env::var("INDEX_CONCURRENCY")
+            .ok()
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|value| *value > 0)
+            .unwrap_or(4);
+        let floorplan_fetch = load_fetch_options();
+        let readiness_timeout = std::env::var("READINESS_TIMEOUT_MS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_PROBE_TIMEOUT);
 
         Ok(Self {
             port,
@@ -31,8 +90,75 @@ impl AppConfig {
             mongodb_db,
             database_url,
             aws_region,
-            s3_bucket_name,
+            object_store,
+            image_store,
             cdn_url,
+            cdn_url_set,
+            scoring_profiles,
+            ann_candidates,
+            structure_index_snapshot,
+            project_change_stream,
+            upsert_chunk_size,
+            index_concurrency,
+            floorplan_fetch,
+            readiness_timeout,
         })
     }
 }
+
+fn load_object_store() -> Result<ObjectStoreConfig> {
+    let bucket = std::env::var("S3_BUCKET_NAME").ok();
+    let kind = std::env::var("OBJECT_STORE").ok();
+
+    Ok(match (kind.as_deref(), bucket) {
+        (Some("s3"), Some(bucket)) => ObjectStoreConfig::S3 { bucket },
+        (Some("s3"), None) => bail!("OBJECT_STORE=s3 requires S3_BUCKET_NAME"),
+        (Some("http") | None, _) => ObjectStoreConfig::Http,
+        (Some("local"), _) => {
+            let Ok(root) = std::env::var("OBJECT_STORE_DIR") else {
+                bail!("OBJECT_STORE=local requires OBJECT_STORE_DIR");
+            };
+            ObjectStoreConfig::Local {
+                root: PathBuf::from(root),
+            }
+        }
+        (Some(other), _) => bail!("unknown OBJECT_STORE {other:?}"),
+    })
+}
+
+/// The CDN cannot list cover images, so they are listed from `S3_BUCKET_NAME` whenever
+/// floorplans come from the CDN and a bucket is configured.
+fn load_image_store(object_store: &ObjectStoreConfig) -> Option<ObjectStoreConfig> {
+    match object_store {
+        ObjectStoreConfig::Http => std::env::var("S3_BUCKET_NAME")
+            .ok()
+            .map(|bucket| ObjectStoreConfig::S3 { bucket }),
+        ObjectStoreConfig::S3 { .. } | ObjectStoreConfig::Local { .. } => None,
+    }
+}
+
+fn load_fetch_options() -> FetchOptions {
+    fn env<T: FromStr>(name: &str) -> Option<T> {
+        std::env::var(name)
+            .ok()
+            .and_then(|value| value.parse::<T>().ok())
+    }
+    let defaults = FetchOptions::default();
+
+    FetchOptions {
+        timeout: env("FLOORPLAN_FETCH_TIMEOUT_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.timeout),
+        max_retries: env("FLOORPLAN_FETCH_RETRIES").unwrap_or(defaults.max_retries),
+        initial_backoff: env("FLOORPLAN_FETCH_BACKOFF_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.initial_backoff),
+        max_backoff: env("FLOORPLAN_FETCH_MAX_BACKOFF_MS")
+            .map(Duration::from_millis)
+            .unwrap_or(defaults.max_backoff),
+        concurrency: env("FLOORPLAN_FETCH_CONCURRENCY")
+            .filter(|value| *value > 0)
+            .unwrap_or(defaults.concurrency),
+        cache_bytes: env("FLOORPLAN_CACHE_BYTES").unwrap_or(defaults.cache_bytes),
+    }
+}
@@ -3,13 +3,49 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use mongodb::error::ErrorKind as MongoErrorKind;
+use sea_orm::DbErr;
 use serde::Serialize;
+use serde_json::{Value, json};
 use thiserror::Error;
 
+use crate::{
+    models::project::structure::index_error::IndexFailureReason,
+    repositories::object_store::ObjectStoreError,
+};
+
 #[derive(Debug, Error)]
 pub enum ApiError {
+    /// The request itself is malformed, e.g. an unparseable path segment or query value.
+    #[error("{message}")]
+    BadRequest {
+        message: String,
+        details: Option<Value>,
+    },
     #[error("{message}")]
     NotFound { message: String },
+    /// The request clashes with work already under way, e.g. a job another request started.
+    #[error("{message}")]
+    Conflict {
+        message: String,
+        details: Option<Value>,
+    },
+    /// The request is well-formed but the data it refers to cannot be processed.
+    #[error("{message}")]
+    Unprocessable {
+        message: String,
+        details: Option<Value>,
+    },
+    /// A database is not configured or cannot be reached.
+    #[error("{message}")]
+    ServiceUnavailable {
+        message: String,
+        #[source]
+        source: Option<anyhow::Error>,
+    },
+    /// The CDN or object store answered with an error or could not be reached.
+    #[error("{message}")]
+    UpstreamFailed { message: String },
     #[error(transparent)]
     Internal {
         #[from]
@@ -19,37 +55,228 @@ pub enum ApiError {
 
 #[derive(Serialize)]
 struct ErrorBody {
+    code: &'static str,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<Value>,
 }
 
 impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::BadRequest {
+            message: message.into(),
+            details: None,
+        }
+    }
+
     pub fn not_found(message: impl Into<String>) -> Self {
         Self::NotFound {
             message: message.into(),
         }
     }
 
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::Conflict {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        Self::Unprocessable {
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn service_unavailable(message: impl Into<String>) -> Self {
+        Self::ServiceUnavailable {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn upstream_failed(message: impl Into<String>) -> Self {
+        Self::UpstreamFailed {
+            message: message.into(),
+        }
+    }
+
     pub fn internal(source: impl Into<anyhow::Error>) -> Self {
         Self::Internal {
             source: source.into(),
         }
     }
+
+    /// Postgres failure; lost or unobtainable connections are reported as unavailable.
+    pub fn database(err: DbErr) -> Self {
+        match err {
+            DbErr::ConnectionAcquire(_) | DbErr::Conn(_) => Self::ServiceUnavailable {
+                message: "Postgres is unavailable".to_string(),
+                source: Some(err.into()),
+            },
+            err => Self::internal(err),
+        }
+    }
+
+    /// Mongo failure; server selection and network errors are reported as unavailable.
+    pub fn mongo(err: mongodb::error::Error) -> Self {
+        match *err.kind {
+            MongoErrorKind::ServerSelection { .. }
+            | MongoErrorKind::Io(_)
+            | MongoErrorKind::ConnectionPoolCleared { .. } => Self::ServiceUnavailable {
+                message: "Mongo is unavailable".to_string(),
+                source: Some(err.into()),
+            },
+            _ => Self::internal(err),
+        }
+    }
+
+    /// Attach machine-readable context; ignored by variants without details.
+    pub fn with_details(mut self, value: Value) -> Self {
+        if let Self::BadRequest { details, .. }
+        | Self::Conflict { details, .. }
+        | Self::Unprocessable { details, .. } = &mut self
+        {
+            *details = Some(value);
+        }
+        self
+    }
+
+    /// Stable identifier clients can branch on instead of parsing messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest { .. } => "bad_request",
+            Self::NotFound { .. } => "not_found",
+            Self::Conflict { .. } => "conflict",
+            Self::Unprocessable { .. } => "unprocessable",
+            Self::ServiceUnavailable { .. } => "service_unavailable",
+            Self::UpstreamFailed { .. } => "upstream_failed",
+            Self::Internal { .. } => "internal",
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            Self::NotFound { .. } => StatusCode::NOT_FOUND,
+            Self::Conflict { .. } => StatusCode::CONFLICT,
+            Self::Unprocessable { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            Self::UpstreamFailed { .. } => StatusCode::BAD_GATEWAY,
+            Self::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        match self {
-            ApiError::NotFound { message } => {
-                let body = Json(ErrorBody { message });
-                (StatusCode::NOT_FOUND, body).into_response()
+        let status = self.status();
+        let code = self.code();
+        let (message, details) = match self {
+            ApiError::BadRequest { message, details }
+            | ApiError::Conflict { message, details }
+            | ApiError::Unprocessable { message, details } => (message, details),
+            ApiError::NotFound { message } => (message, None),
+            ApiError::ServiceUnavailable { message, source } => {
+                match &source {
+                    Some(source) => tracing::warn!(error = %source, "{message}"),
+                    None => tracing::warn!("{message}"),
+                }
+                (message, None)
+            }
+            ApiError::UpstreamFailed { message } => {
+                tracing::warn!(error = %message, "Upstream request failed");
+                (message, None)
             }
             ApiError::Internal { source } => {
                 tracing::error!(error = %source, "Unexpected internal failure");
-                let body = Json(ErrorBody {
-                    message: "unexpected error".to_string(),
-                });
-                (StatusCode::INTERNAL_SERVER_ERROR, body).into_response()
+                ("unexpected error".to_string(), None)
             }
+        };
+
+        let body = Json(ErrorBody {
+            code,
+            message,
+            details,
+        });
+        (status, body).into_response()
+    }
+}
+
+impl From<ObjectStoreError> for ApiError {
+    fn from(err: ObjectStoreError) -> Self {
+        Self::upstream_failed(err.to_string())
+    }
+}
+
+/// A project that could not be indexed, with a reason that can be triaged.
+#[derive(Debug, Error)]
+#[error("{message}")]
+pub struct IndexFailure {
+    pub reason: IndexFailureReason,
+    pub message: String,
+    /// The API error this failure was raised from, returned as-is to HTTP callers.
+    #[source]
+    cause: Option<Box<ApiError>>,
+}
+
+impl IndexFailure {
+    pub fn new(reason: IndexFailureReason, message: impl Into<String>) -> Self {
+        Self {
+            reason,
+            message: message.into(),
+            cause: None,
+        }
+    }
+
+    /// Whether indexing the same project again later may succeed, e.g. after a CDN or
+    /// database outage. Bad documents and missing projects fail the same way every time.
+    pub fn is_transient(&self) -> bool {
+        match self.reason {
+            IndexFailureReason::UpstreamError => true,
+            IndexFailureReason::Internal => !matches!(
+                self.cause.as_deref(),
+                Some(
+                    ApiError::NotFound { .. }
+                        | ApiError::BadRequest { .. }
+                        | ApiError::Unprocessable { .. }
+                )
+            ),
+            IndexFailureReason::MissingField
+            | IndexFailureReason::BadGeometry
+            | IndexFailureReason::ParseError => false,
+        }
+    }
+}
+
+impl From<ApiError> for IndexFailure {
+    fn from(err: ApiError) -> Self {
+        let reason = match err {
+            ApiError::UpstreamFailed { .. } => IndexFailureReason::UpstreamError,
+            _ => IndexFailureReason::Internal,
+        };
+        Self {
+            reason,
+            message: err.to_string(),
+            cause: Some(Box::new(err)),
+        }
+    }
+}
+
+impl From<IndexFailure> for ApiError {
+    fn from(failure: IndexFailure) -> Self {
+        if let Some(cause) = failure.cause {
+            return *cause;
+        }
+
+        match failure.reason {
+            IndexFailureReason::MissingField
+            | IndexFailureReason::BadGeometry
+            | IndexFailureReason::ParseError => ApiError::unprocessable(failure.message)
+                .with_details(json!({ "reason": failure.reason })),
+            IndexFailureReason::UpstreamError => ApiError::upstream_failed(failure.message),
+            IndexFailureReason::Internal => ApiError::internal(failure),
         }
     }
 }
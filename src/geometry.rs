@@ -0,0 +1,5 @@
+pub mod fourier;
+pub mod polygon;
+
+pub use fourier::{descriptor_distance, fourier_descriptor};
+pub use polygon::{Point2, Polygon};
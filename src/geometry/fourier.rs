@@ -0,0 +1,93 @@
+use std::f64::consts::PI;
+
+use crate::geometry::{Point2, Polygon};
+
+/// Number of outline samples fed into the DFT.
+const SAMPLE_COUNT: usize = 64;
+/// Number of harmonics kept in the descriptor.
+pub const DESCRIPTOR_LEN: usize = 16;
+
+/// Fourier descriptor of a closed outline.
+///
+/// The outline is resampled at equal arc-length steps and transformed as a complex sequence.
+/// Dropping the DC term removes translation, using magnitudes removes rotation and the start
+/// point, folding `|F(k)| + |F(-k)|` removes mirroring, and dividing by the first harmonic
+/// removes scale. Harmonics `2..=DESCRIPTOR_LEN + 1` are returned.
+pub fn fourier_descriptor(polygon: &Polygon) -> Option<Vec<f64>> {
+    let samples = resample(polygon, SAMPLE_COUNT)?;
+    let n = samples.len() as f64;
+
+    let magnitude = |k: i64| -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (t, point) in samples.iter().enumerate() {
+            let angle = -2.0 * PI * (k as f64) * (t as f64) / n;
+            let (sin, cos) = angle.sin_cos();
+            re += point.x * cos - point.y * sin;
+            im += point.x * sin + point.y * cos;
+        }
+        re.hypot(im) / n
+    };
+    let harmonic = |k: i64| magnitude(k) + magnitude(-k);
+
+    let base = harmonic(1);
+    if base <= f64::EPSILON {
+        return None;
+    }
+
+    Some(
+        (2..=DESCRIPTOR_LEN as i64 + 1)
+            .map(|k| harmonic(k) / base)
+            .collect(),
+    )
+}
+
+/// Euclidean distance between two descriptors, reading harmonics missing from the shorter one
+/// as zero.
+///
+/// `RoomStructureRepository` evaluates the same distance in SQL; keep the two in step.
+pub fn descriptor_distance(a: &[f64], b: &[f64]) -> f64 {
+    let harmonic = |descriptor: &[f64], k: usize| descriptor.get(k).copied().unwrap_or_default();
+    (0..a.len().max(b.len()))
+        .map(|k| harmonic(a, k) - harmonic(b, k))
+        .map(|difference| difference * difference)
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Resample the closed outline into `count` points evenly spaced along its perimeter.
+fn resample(polygon: &Polygon, count: usize) -> Option<Vec<Point2>> {
+    let points = &polygon.points;
+    let perimeter = polygon.perimeter();
+    if points.len() < 3 || perimeter <= f64::EPSILON || count == 0 {
+        return None;
+    }
+
+    let step = perimeter / count as f64;
+    let mut samples = Vec::with_capacity(count);
+    let mut edge = 0;
+    let mut edge_start = 0.0;
+    for i in 0..count {
+        let target = step * i as f64;
+        loop {
+            let a = points[edge];
+            let b = points[(edge + 1) % points.len()];
+            let length = a.distance(&b);
+            if target <= edge_start + length || edge + 1 == points.len() {
+                let t = if length > 0.0 {
+                    ((target - edge_start) / length).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                samples.push(Point2::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t));
+                break;
+            }
+            edge_start += length;
+            edge += 1;
+        }
+    }
+
+    Some(samples)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,88 @@
+use std::f64::consts::FRAC_PI_3;
+
+use super::{DESCRIPTOR_LEN, descriptor_distance, fourier_descriptor};
+use crate::geometry::{Point2, Polygon};
+
+/// 4×4 square with its top-right 2×2 quadrant removed.
+fn l_shape() -> Vec<Point2> {
+    [
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 2.0),
+        (2.0, 2.0),
+        (2.0, 4.0),
+        (0.0, 4.0),
+    ]
+    .into_iter()
+    .map(|(x, y)| Point2::new(x, y))
+    .collect()
+}
+
+fn descriptor(points: Vec<Point2>) -> Vec<f64> {
+    fourier_descriptor(&Polygon::new(points)).expect("descriptor")
+}
+
+fn assert_close(a: &[f64], b: &[f64]) {
+    let distance = descriptor_distance(a, b);
+    assert!(distance < 1e-9, "descriptors differ by {distance}");
+}
+
+#[test]
+fn descriptor_has_the_documented_length() {
+    assert_eq!(descriptor(l_shape()).len(), DESCRIPTOR_LEN);
+}
+
+#[test]
+fn descriptor_ignores_placement_scale_and_mirroring() {
+    let original = descriptor(l_shape());
+    let (sin, cos) = FRAC_PI_3.sin_cos();
+
+    let moved = l_shape()
+        .into_iter()
+        .map(|p| {
+            Point2::new(
+                3.0 * (p.x * cos - p.y * sin) + 10.0,
+                3.0 * (p.x * sin + p.y * cos) - 7.0,
+            )
+        })
+        .collect();
+    let mirrored = l_shape()
+        .into_iter()
+        .map(|p| Point2::new(-p.x, p.y))
+        .collect();
+    let mut reversed = l_shape();
+    reversed.reverse();
+
+    assert_close(&original, &descriptor(moved));
+    assert_close(&original, &descriptor(mirrored));
+    assert_close(&original, &descriptor(reversed));
+}
+
+#[test]
+fn descriptor_tells_shapes_apart() {
+    let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]
+        .into_iter()
+        .map(|(x, y)| Point2::new(x, y))
+        .collect();
+
+    assert!(descriptor_distance(&descriptor(l_shape()), &descriptor(square)) > 0.01);
+}
+
+#[test]
+fn degenerate_outlines_have_no_descriptor() {
+    let line = Polygon::new(vec![Point2::new(0.0, 0.0), Point2::new(4.0, 0.0)]);
+    let point = Polygon::new(vec![Point2::new(1.0, 1.0); 4]);
+
+    assert_eq!(fourier_descriptor(&line), None);
+    assert_eq!(fourier_descriptor(&point), None);
+    assert_eq!(fourier_descriptor(&Polygon::default()), None);
+}
+
+#[test]
+fn distance_reads_missing_harmonics_as_zero() {
+    assert_eq!(descriptor_distance(&[3.0, 4.0], &[3.0, 4.0]), 0.0);
+    assert_eq!(descriptor_distance(&[0.0, 0.0], &[3.0, 4.0]), 5.0);
+    assert_eq!(descriptor_distance(&[], &[3.0, 4.0]), 5.0);
+    assert_eq!(descriptor_distance(&[3.0], &[3.0, 4.0]), 4.0);
+    assert_eq!(descriptor_distance(&[], &[]), 0.0);
+}
@@ -0,0 +1,271 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Point2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point2 {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn distance(&self, other: &Self) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    fn cross(origin: &Self, a: &Self, b: &Self) -> f64 {
+        (a.x - origin.x) * (b.y - origin.y) - (a.y - origin.y) * (b.x - origin.x)
+    }
+}
+
+/// Rectangle enclosing a point set, aligned to an arbitrary orientation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrientedRect {
+    /// Longer side.
+    pub length: f64,
+    /// Shorter side.
+    pub breadth: f64,
+    /// Angle in radians between the x axis and the longer side.
+    pub angle: f64,
+}
+
+impl OrientedRect {
+    pub fn area(&self) -> f64 {
+        self.length * self.breadth
+    }
+
+    /// Length over breadth, always `>= 1.0` (infinite for degenerate rectangles).
+    pub fn aspect(&self) -> f64 {
+        if self.breadth > 0.0 {
+            self.length / self.breadth
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Simple polygon given by its vertices in order; the closing edge is implicit.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Polygon {
+    pub points: Vec<Point2>,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point2>) -> Self {
+        Self { points }
+    }
+
+    pub fn is_degenerate(&self) -> bool {
+        self.points.len() < 3 || self.area() <= f64::EPSILON
+    }
+
+    /// Shoelace area, positive for counter-clockwise winding.
+    pub fn signed_area(&self) -> f64 {
+        let n = self.points.len();
+        if n < 3 {
+            return 0.0;
+        }
+
+        let twice_area: f64 = (0..n)
+            .map(|i| {
+                let a = &self.points[i];
+                let b = &self.points[(i + 1) % n];
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        twice_area / 2.0
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        let n = self.points.len();
+        if n < 2 {
+            return 0.0;
+        }
+
+        (0..n)
+            .map(|i| self.points[i].distance(&self.points[(i + 1) % n]))
+            .sum()
+    }
+
+    /// Whether any two non-adjacent edges touch or cross, i.e. the outline is not simple.
+    ///
+    /// Repeated consecutive vertices, including an explicit closing vertex, are ignored: they
+    /// only add zero-length edges that would otherwise touch their neighbours' neighbours.
+    pub fn is_self_intersecting(&self) -> bool {
+        let points = self.distinct_vertices();
+        let n = points.len();
+        if n < 4 {
+            return false;
+        }
+
+        let edge = |i: usize| (points[i], points[(i + 1) % n]);
+        (0..n).any(|i| {
+            (i + 2..n)
+                .filter(|&j| (j + 1) % n != i)
+                .any(|j| segments_intersect(edge(i), edge(j)))
+        })
+    }
+
+    /// Vertices with consecutive duplicates removed, treating the outline as closed.
+    fn distinct_vertices(&self) -> Vec<Point2> {
+        let mut points = self.points.clone();
+        points.dedup();
+        while points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        points
+    }
+
+    /// Convex hull in counter-clockwise order (Andrew's monotone chain).
+    pub fn convex_hull(&self) -> Polygon {
+        let mut points = self.points.clone();
+        points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+        points.dedup();
+
+        if points.len() < 3 {
+            return Polygon::new(points);
+        }
+
+        let mut lower: Vec<Point2> = Vec::with_capacity(points.len());
+        for point in &points {
+            while lower.len() >= 2
+                && Point2::cross(&lower[lower.len() - 2], &lower[lower.len() - 1], point) <= 0.0
+            {
+                lower.pop();
+            }
+            lower.push(*point);
+        }
+
+        let mut upper: Vec<Point2> = Vec::with_capacity(points.len());
+        for point in points.iter().rev() {
+            while upper.len() >= 2
+                && Point2::cross(&upper[upper.len() - 2], &upper[upper.len() - 1], point) <= 0.0
+            {
+                upper.pop();
+            }
+            upper.push(*point);
+        }
+
+        lower.pop();
+        upper.pop();
+        lower.extend(upper);
+        Polygon::new(lower)
+    }
+
+    /// Ratio of the polygon area to its convex hull area; `1.0` for convex shapes.
+    pub fn convexity(&self) -> f64 {
+        let hull_area = self.convex_hull().area();
+        if hull_area > 0.0 {
+            self.area() / hull_area
+        } else {
+            0.0
+        }
+    }
+
+    /// Isoperimetric quotient `4πA / P²`; `1.0` for a circle, `π/4` for a square.
+    pub fn compactness(&self) -> f64 {
+        let perimeter = self.perimeter();
+        if perimeter > 0.0 {
+            4.0 * PI * self.area() / (perimeter * perimeter)
+        } else {
+            0.0
+        }
+    }
+
+    /// Minimum-area enclosing rectangle.
+    ///
+    /// One side of the optimal rectangle is collinear with a convex hull edge, so only the hull
+    /// edge orientations need to be evaluated (rotating calipers).
+    pub fn minimum_area_rectangle(&self) -> Option<OrientedRect> {
+        let hull = self.convex_hull();
+        let n = hull.points.len();
+        if n == 0 {
+            return None;
+        }
+        if n < 3 {
+            let (a, b) = (hull.points[0], hull.points[n - 1]);
+            return Some(OrientedRect {
+                length: a.distance(&b),
+                breadth: 0.0,
+                angle: (b.y - a.y).atan2(b.x - a.x),
+            });
+        }
+
+        let mut best: Option<OrientedRect> = None;
+        for i in 0..n {
+            let a = hull.points[i];
+            let b = hull.points[(i + 1) % n];
+            let edge_length = a.distance(&b);
+            if edge_length <= f64::EPSILON {
+                continue;
+            }
+
+            let (ux, uy) = ((b.x - a.x) / edge_length, (b.y - a.y) / edge_length);
+            let (mut min_u, mut max_u) = (f64::INFINITY, f64::NEG_INFINITY);
+            let (mut min_v, mut max_v) = (f64::INFINITY, f64::NEG_INFINITY);
+            for point in &hull.points {
+                let u = point.x * ux + point.y * uy;
+                let v = -point.x * uy + point.y * ux;
+                min_u = min_u.min(u);
+                max_u = max_u.max(u);
+                min_v = min_v.min(v);
+                max_v = max_v.max(v);
+            }
+
+            let (along, across) = (max_u - min_u, max_v - min_v);
+            let angle = uy.atan2(ux);
+            let candidate = if along >= across {
+                OrientedRect {
+                    length: along,
+                    breadth: across,
+                    angle,
+                }
+            } else {
+                OrientedRect {
+                    length: across,
+                    breadth: along,
+                    angle: angle + std::f64::consts::FRAC_PI_2,
+                }
+            };
+
+            if best.is_none_or(|best| candidate.area() < best.area()) {
+                best = Some(candidate);
+            }
+        }
+
+        best
+    }
+}
+
+/// Closed-segment intersection test, counting collinear overlaps and touching endpoints.
+fn segments_intersect((a, b): (Point2, Point2), (c, d): (Point2, Point2)) -> bool {
+    let d1 = Point2::cross(&c, &d, &a);
+    let d2 = Point2::cross(&c, &d, &b);
+    let d3 = Point2::cross(&a, &b, &c);
+    let d4 = Point2::cross(&a, &b, &d);
+
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+
+    let within = |p: &Point2, q: &Point2, r: &Point2| {
+        r.x >= p.x.min(q.x) && r.x <= p.x.max(q.x) && r.y >= p.y.min(q.y) && r.y <= p.y.max(q.y)
+    };
+    (d1 == 0.0 && within(&c, &d, &a))
+        || (d2 == 0.0 && within(&c, &d, &b))
+        || (d3 == 0.0 && within(&a, &b, &c))
+        || (d4 == 0.0 && within(&a, &b, &d))
+}
+
+#[cfg(test)]
+mod tests;
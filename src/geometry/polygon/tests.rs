@@ -0,0 +1,105 @@
+use std::f64::consts::PI;
+
+use super::{Point2, Polygon};
+
+fn polygon(points: &[(f64, f64)]) -> Polygon {
+    Polygon::new(points.iter().map(|&(x, y)| Point2::new(x, y)).collect())
+}
+
+fn square() -> Polygon {
+    polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)])
+}
+
+/// 4×4 square with its top-right 2×2 quadrant removed.
+fn l_shape() -> Polygon {
+    polygon(&[
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 2.0),
+        (2.0, 2.0),
+        (2.0, 4.0),
+        (0.0, 4.0),
+    ])
+}
+
+#[test]
+fn shoelace_area_follows_winding() {
+    let counter_clockwise = square();
+    let clockwise = Polygon::new(counter_clockwise.points.iter().rev().copied().collect());
+
+    assert_eq!(counter_clockwise.signed_area(), 16.0);
+    assert_eq!(clockwise.signed_area(), -16.0);
+    assert_eq!(clockwise.area(), 16.0);
+    assert_eq!(l_shape().area(), 12.0);
+    assert_eq!(polygon(&[(0.0, 0.0), (1.0, 1.0)]).area(), 0.0);
+}
+
+#[test]
+fn convex_hull_drops_interior_and_collinear_points() {
+    let points = polygon(&[
+        (0.0, 0.0),
+        (2.0, 0.0),
+        (4.0, 0.0),
+        (1.0, 1.0),
+        (4.0, 4.0),
+        (0.0, 4.0),
+        (0.0, 4.0),
+    ]);
+
+    let hull = points.convex_hull();
+    assert_eq!(
+        hull.points,
+        vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(4.0, 0.0),
+            Point2::new(4.0, 4.0),
+            Point2::new(0.0, 4.0),
+        ]
+    );
+    assert!(hull.signed_area() > 0.0);
+}
+
+#[test]
+fn convexity_is_area_over_hull_area() {
+    assert_eq!(square().convexity(), 1.0);
+    assert_eq!(l_shape().convexity(), 12.0 / 14.0);
+    assert_eq!(polygon(&[(0.0, 0.0), (1.0, 0.0)]).convexity(), 0.0);
+}
+
+#[test]
+fn compactness_is_scale_invariant() {
+    let scaled = polygon(&[(0.0, 0.0), (400.0, 0.0), (400.0, 400.0), (0.0, 400.0)]);
+
+    assert!((square().compactness() - PI / 4.0).abs() < 1e-12);
+    assert!((scaled.compactness() - square().compactness()).abs() < 1e-12);
+    assert!((l_shape().compactness() - 4.0 * PI * 12.0 / 256.0).abs() < 1e-12);
+    assert_eq!(Polygon::default().compactness(), 0.0);
+}
+
+#[test]
+fn crossing_edges_are_self_intersecting() {
+    let bowtie = polygon(&[(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0)]);
+
+    assert!(bowtie.is_self_intersecting());
+    assert!(!square().is_self_intersecting());
+    assert!(!l_shape().is_self_intersecting());
+}
+
+#[test]
+fn repeated_vertices_are_not_self_intersections() {
+    let closed = polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 0.0)]);
+    let repeated = polygon(&[
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 4.0),
+        (0.0, 4.0),
+        (0.0, 4.0),
+        (0.0, 0.0),
+    ]);
+    let closed_bowtie = polygon(&[(0.0, 0.0), (4.0, 4.0), (4.0, 0.0), (0.0, 4.0), (0.0, 0.0)]);
+
+    assert!(!closed.is_self_intersecting());
+    assert!(!repeated.is_self_intersecting());
+    assert!(closed_bowtie.is_self_intersecting());
+}
@@ -1 +1,4 @@
+pub mod health_handler;
+pub mod job_handler;
+pub mod metrics_handler;
 pub mod project_handler;
@@ -0,0 +1,45 @@
+use axum::{Json, extract::State, http::StatusCode};
+use serde::Serialize;
+
+use crate::{
+    repositories::dependency_probe::{DependencyState, DependencyStatus},
+    state::AppState,
+};
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    pub status: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessResponse {
+    pub status: &'static str,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+/// Liveness: the process is up and serving requests.
+pub async fn get_health() -> Json<HealthResponse> {
+    Json(HealthResponse { status: "ok" })
+}
+
+/// Readiness: 503 while any configured dependency is unreachable.
+pub async fn get_readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let dependencies = state.dependency_probe.check().await;
+    let ready = dependencies
+        .iter()
+        .all(|dependency| dependency.status != DependencyState::Down);
+
+    let (status, label) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+    (
+        status,
+        Json(ReadinessResponse {
+            status: label,
+            dependencies,
+        }),
+    )
+}
@@ -0,0 +1,23 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use uuid::Uuid;
+
+use crate::{error::ApiError, routes::job::dto::JobResponse, state::AppState};
+
+pub async fn get_job(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<JobResponse>, ApiError> {
+    let job_id = Uuid::parse_str(&job_id)
+        .map_err(|_| ApiError::bad_request(format!("invalid job identifier: {job_id}")))?;
+    let job = state
+        .job_repository()
+        .find_by_id(job_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("job {job_id} not found")))?;
+
+    let response = JobResponse::try_from_job(job).map_err(ApiError::internal)?;
+    Ok(Json(response))
+}
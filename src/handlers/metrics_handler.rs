@@ -0,0 +1,16 @@
+use axum::{Json, extract::State};
+use serde::Serialize;
+
+use crate::{repositories::floorplan_fetcher::FetchMetricsSnapshot, state::AppState};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsResponse {
+    pub floorplan_fetch: FetchMetricsSnapshot,
+}
+
+pub async fn get_metrics(State(state): State<AppState>) -> Json<MetricsResponse> {
+    Json(MetricsResponse {
+        floorplan_fetch: state.floorplan_fetcher.metrics(),
+    })
+}
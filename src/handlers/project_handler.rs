@@ -1,43 +1,83 @@
-use aws_sdk_s3::Client as S3Client;
 use axum::{
     Json,
     extract::{Path, Query, State},
     http::StatusCode as AxumStatusCode,
 };
-use reqwest::{Client as HttpClient, StatusCode as HttpStatusCode};
-use std::{
-    borrow::ToOwned,
-    collections::{HashMap, HashSet},
-};
+use std::collections::{HashMap, HashSet};
 
 use serde::Deserialize;
 
 use crate::{
-    error::ApiError,
+    error::{ApiError, IndexFailure},
+    geometry::{Point2, Polygon},
     models::{
         image::Image as ProjectImage,
         project::{
             Project,
-            child::{floorplan::Floorplan, structure::BoundingBox},
+            child::{
+                floorplan::Floorplan,
+                structure::{BoundingBox, RoomGraph, RoomShape},
+                validate_floorplan,
+            },
+            structure::{floor_structure, index_error::IndexFailureReason, room_structure},
         },
     },
     repositories::{
-        floor_structure_repository::FloorStructureRecord, project_repository::ProjectRepository,
+        floor_structure_repository::FloorStructureRecord,
+        object_store::{ObjectStore, ObjectStoreError},
+        project_repository::ProjectStore,
         room_structure_repository::RoomStructureRecord,
+        structure_store::replace_project_structures,
     },
+    routes::job::dto::JobResponse,
     routes::project::dto::{
-        FloorResponse, ProjectRenderingImageResponse, ProjectRenderingsResponse, ProjectResponse,
-        RoomItemsResponse, RoomResponse,
+        FloorResponse, FloorplanValidationResponse, PageResponse, ProjectRenderingImageResponse,
+        ProjectRenderingsResponse, ProjectResponse, RoomItemsResponse, RoomResponse,
+        SimilarRoomRequest, SimilarityExplanation, StructureIndexResponse,
     },
     state::AppState,
+    utils::cursor::SimilarityCursor,
+    workers::structure_jobs,
 };
 
+const DEFAULT_SIMILAR_LIMIT: u64 = 10;
+const MAX_SIMILAR_LIMIT: u64 = 100;
+
 #[derive(Debug, Default, Deserialize)]
-pub struct AreaRangeQuery {
+pub struct SimilarQuery {
     #[serde(rename = "areaFrom")]
     area_from: Option<i32>,
     #[serde(rename = "areaTo")]
     area_to: Option<i32>,
+    limit: Option<u64>,
+    cursor: Option<String>,
+    profile: Option<String>,
+    #[serde(default)]
+    explain: bool,
+}
+
+impl SimilarQuery {
+    fn limit(&self) -> u64 {
+        self.limit
+            .unwrap_or(DEFAULT_SIMILAR_LIMIT)
+            .clamp(1, MAX_SIMILAR_LIMIT)
+    }
+
+    /// Decode the cursor, rejecting one issued for a different ranking than `ranking`.
+    fn cursor(&self, ranking: u64) -> Result<Option<SimilarityCursor>, ApiError> {
+        let Some(token) = self.cursor.as_deref().filter(|token| !token.is_empty()) else {
+            return Ok(None);
+        };
+        let cursor = SimilarityCursor::decode(token)
+            .map_err(|_| ApiError::bad_request(format!("invalid cursor: {token}")))?;
+        if cursor.ranking != ranking {
+            return Err(ApiError::bad_request(
+                "cursor was issued for a different query, profile or area band",
+            ));
+        }
+
+        Ok(Some(cursor))
+    }
 }
 
 pub async fn get_project_by_id(
@@ -46,12 +86,11 @@ pub async fn get_project_by_id(
 ) -> Result<Json<ProjectResponse>, ApiError> {
     let repository = state.project_repository()?;
     let mut project = repository.get_by_id(&project_id).await?;
-    populate_floorplans(&state.http_client, &state.cdn_base_url, &mut project).await?;
+    state.floorplan_fetcher.populate(&mut project).await?;
     ensure_default_cover_image(
         repository,
         &mut project,
-        state.s3_client.as_ref(),
-        state.s3_bucket.as_deref(),
+        state.image_store.as_ref(),
         &state.cdn_base_url,
     )
     .await?;
@@ -95,17 +134,17 @@ pub async fn get_room_items(
 ) -> Result<Json<RoomItemsResponse>, ApiError> {
     let (project_id_raw, room_id) = room_key
         .split_once('_')
-        .ok_or_else(|| ApiError::not_found(format!("invalid room identifier: {room_key}")))?;
+        .ok_or_else(|| ApiError::bad_request(format!("invalid room identifier: {room_key}")))?;
 
     if project_id_raw.is_empty() || room_id.is_empty() {
-        return Err(ApiError::not_found(format!(
+        return Err(ApiError::bad_request(format!(
             "invalid room identifier: {room_key}"
         )));
     }
 
     let project_repository = state.project_repository()?;
     let mut project = project_repository.get_by_id(project_id_raw).await?;
-    populate_floorplans(&state.http_client, &state.cdn_base_url, &mut project).await?;
+    state.floorplan_fetcher.populate(&mut project).await?;
 
     let Some((floorplan, room)) = project.floorplans.iter().find_map(|floorplan| {
         floorplan.rooms.as_ref().and_then(|rooms| {
@@ -153,86 +192,193 @@ pub async fn get_room_items(
     Ok(Json(response))
 }
 
-pub async fn create_project_structure(
+pub async fn validate_project_floorplans(
     State(state): State<AppState>,
     Path(project_id): Path<String>,
-) -> Result<AxumStatusCode, ApiError> {
-    let project_repository = state.project_repository()?;
-    let floor_structure_repository = state.floor_structure_repository()?;
-    let room_structure_repository = state.room_structure_repository()?;
+) -> Result<Json<FloorplanValidationResponse>, ApiError> {
+    let repository = state.project_repository()?;
+    let mut project = repository.get_by_id(&project_id).await?;
+    state.floorplan_fetcher.populate(&mut project).await?;
 
-    let mut project = project_repository.get_by_id(&project_id).await?;
-    populate_floorplans(&state.http_client, &state.cdn_base_url, &mut project).await?;
+    let issues = project
+        .floorplans
+        .iter()
+        .flat_map(validate_floorplan)
+        .collect();
 
-    let (floor_records, room_records) = build_structure_records(&project_id, &project.floorplans)?;
+    Ok(Json(FloorplanValidationResponse::new(
+        project_id,
+        project.floorplans.len(),
+        issues,
+    )))
+}
 
-    floor_structure_repository.save_all(floor_records).await?;
-    room_structure_repository.save_all(room_records).await?;
+pub async fn create_project_structure(
+    State(state): State<AppState>,
+    Path(project_id): Path<String>,
+) -> Result<Json<StructureIndexResponse>, ApiError> {
+    let response = reindex_project(&state, &project_id).await?;
 
-    Ok(AxumStatusCode::NO_CONTENT)
+    Ok(Json(response))
 }
 
-pub async fn create_recent_project_structures(
+pub async fn delete_project_structure(
     State(state): State<AppState>,
+    Path(project_id): Path<String>,
 ) -> Result<AxumStatusCode, ApiError> {
-    const RECENT_LIMIT: i64 = 300;
+    delete_project_structures(&state, &project_id).await?;
 
-    let project_repository = state.project_repository()?;
-    let floor_structure_repository = state.floor_structure_repository()?;
-    let room_structure_repository = state.room_structure_repository()?;
+    Ok(AxumStatusCode::NO_CONTENT)
+}
 
-    let ids = project_repository.find_recent_ids(RECENT_LIMIT).await?;
+/// Queue a rebuild of every changed project, or of the most recent ones without a watermark store.
+pub async fn create_recent_project_structures(
+    State(state): State<AppState>,
+) -> Result<(AxumStatusCode, Json<JobResponse>), ApiError> {
+    let kind = structure_jobs::changed_projects_job(&state);
+    let job = structure_jobs::enqueue(&state, kind).await?;
 
-    let mut floor_records = Vec::new();
-    let mut room_records = Vec::new();
+    let response = JobResponse::try_from_job(job).map_err(ApiError::internal)?;
+    Ok((AxumStatusCode::ACCEPTED, Json(response)))
+}
 
-    for project_id in ids {
-        let mut project = project_repository.get_by_id(&project_id).await?;
-        populate_floorplans(&state.http_client, &state.cdn_base_url, &mut project).await?;
+/// Replace the stored structures of `project_id` with ones built from its current floorplans.
+///
+/// Floors and rooms removed in the editor are deleted along the way.
+pub async fn reindex_project(
+    state: &AppState,
+    project_id: &str,
+) -> Result<StructureIndexResponse, IndexFailure> {
+    let project_repository = state.project_repository()?;
+    let floor_structure_repository = state.floor_structure_repository();
+    let room_structure_repository = state.room_structure_repository();
 
-        if project.floorplans.is_empty() {
-            continue;
-        }
+    let mut project = project_repository.get_by_id(project_id).await?;
+    state.floorplan_fetcher.populate(&mut project).await?;
 
-        let (project_floor_records, project_room_records) =
-            build_structure_records(&project_id, &project.floorplans)?;
+    let (floor_records, room_records) = build_structure_records(project_id, &project.floorplans)?;
 
-        floor_records.extend(project_floor_records);
-        room_records.extend(project_room_records);
-    }
+    let (floors, rooms) = replace_project_structures(
+        floor_structure_repository,
+        room_structure_repository,
+        project_id,
+        floor_records,
+        room_records,
+    )
+    .await?;
 
-    floor_structure_repository.save_all(floor_records).await?;
-    room_structure_repository.save_all(room_records).await?;
+    Ok(StructureIndexResponse {
+        indexed_projects: u64::from(!project.floorplans.is_empty()),
+        floors,
+        rooms,
+    })
+}
 
-    Ok(AxumStatusCode::NO_CONTENT)
+/// Remove every floor and room structure of `project_id`.
+///
+/// Replacing both with nothing reuses the shared transaction of a reindex, so a failure never
+/// leaves the project half removed.
+pub async fn delete_project_structures(state: &AppState, project_id: &str) -> Result<(), ApiError> {
+    replace_project_structures(
+        state.floor_structure_repository(),
+        state.room_structure_repository(),
+        project_id,
+        Vec::new(),
+        Vec::new(),
+    )
+    .await?;
+
+    Ok(())
 }
 
 pub async fn get_similar_floors(
     State(state): State<AppState>,
     Path(floor_id): Path<String>,
-    Query(query): Query<AreaRangeQuery>,
-) -> Result<Json<Vec<FloorResponse>>, ApiError> {
-    const SIMILAR_LIMIT: u64 = 10;
-
-    let project_repository = state.project_repository()?;
-    let floor_structure_repository = state.floor_structure_repository()?;
-    let image_repository = state.image_repository()?;
-
-    let floor = floor_structure_repository
+    Query(query): Query<SimilarQuery>,
+) -> Result<Json<PageResponse<FloorResponse>>, ApiError> {
+    let floor = state
+        .floor_structure_repository()
         .find_by_id(&floor_id)
         .await?
         .ok_or_else(|| ApiError::not_found(format!("floor {floor_id} not found")))?;
 
+    let response = rank_similar_floors(&state, &query, &floor).await?;
+    Ok(Json(response))
+}
+
+/// Rank indexed floors against a floorplan that has not been saved, e.g. one still being drawn.
+///
+/// A floorplan sent without an `area` is measured from its room outlines.
+pub async fn search_similar_floors(
+    State(state): State<AppState>,
+    Query(query): Query<SimilarQuery>,
+    Json(mut floorplan): Json<Floorplan>,
+) -> Result<Json<PageResponse<FloorResponse>>, ApiError> {
+    if floorplan.title.is_none() {
+        floorplan.title = Some(String::new());
+    }
+    if floorplan.area.is_none() {
+        floorplan.area = Some(outline_area(&floorplan)?);
+    }
+
+    let record = build_floor_structure_records("", std::slice::from_ref(&floorplan))?
+        .pop()
+        .ok_or_else(|| ApiError::unprocessable("floorplan produced no structure"))?;
+    let floor = floor_structure::Model::from(record);
+
+    let response = rank_similar_floors(&state, &query, &floor).await?;
+    Ok(Json(response))
+}
+
+/// Area in square metres enclosed by the room outlines of `floorplan`.
+fn outline_area(floorplan: &Floorplan) -> Result<f64, ApiError> {
+    let rooms = floorplan.rooms.as_deref().unwrap_or_default();
+    if rooms.is_empty() {
+        return Err(ApiError::unprocessable(
+            "floorplan area is missing and there are no rooms to measure it from",
+        ));
+    }
+
+    let mut area = 0.0;
+    for room in rooms {
+        let shape = RoomShape::from_room(floorplan, room).map_err(|err| {
+            ApiError::unprocessable(format!(
+                "floorplan area is missing and cannot be measured: {err}"
+            ))
+        })?;
+        area += shape.area;
+    }
+
+    Ok(area / 1_000_000.0)
+}
+
+async fn rank_similar_floors(
+    state: &AppState,
+    query: &SimilarQuery,
+    floor: &floor_structure::Model,
+) -> Result<PageResponse<FloorResponse>, ApiError> {
+    let limit = query.limit();
+    let scoring = &state.scoring_profile(query.profile.as_deref())?.floor;
+
+    let project_repository = state.project_repository()?;
+    let floor_structure_repository = state.floor_structure_repository();
+    let image_repository = state.image_repository()?;
+
     let area_from = query
         .area_from
         .map(|value| value as f64)
-        .unwrap_or(floor.area * 0.85);
+        .unwrap_or(floor.area * (1.0 - scoring.area_tolerance));
     let area_to = query
         .area_to
         .map(|value| value as f64)
-        .unwrap_or(floor.area * 1.15);
+        .unwrap_or(floor.area * (1.0 + scoring.area_tolerance));
+    let ranking =
+        SimilarityCursor::fingerprint(&format!("{floor:?}|{scoring:?}|{area_from}|{area_to}"));
+    let cursor = query.cursor(ranking)?;
+
+    let graph_signature = floor.decode_graph_signature();
 
-    let similar_floors = floor_structure_repository
+    let mut similar_floors = floor_structure_repository
         .find_top_k_similar_floors(
             &floor.project_id,
             floor.area,
@@ -241,12 +387,27 @@ pub async fn get_similar_floors(
             area_to,
             floor.bounding_box_aspect,
             floor.rectangularity,
-            SIMILAR_LIMIT,
+            graph_signature.as_ref(),
+            scoring,
+            limit + 1,
+            cursor.as_ref(),
         )
         .await?;
 
+    let next_cursor = if similar_floors.len() as u64 > limit {
+        similar_floors.truncate(limit as usize);
+        let offset = cursor.as_ref().map_or(0, |cursor| cursor.offset) + limit;
+        similar_floors.last().map(|record| {
+            SimilarityCursor::new(record.score, &record.id, offset)
+                .with_ranking(ranking)
+                .encode()
+        })
+    } else {
+        None
+    };
+
     if similar_floors.is_empty() {
-        return Ok(Json(Vec::new()));
+        return Ok(PageResponse::new(Vec::new(), next_cursor));
     }
 
     let mut project_ids: Vec<String> = similar_floors
@@ -261,8 +422,7 @@ pub async fn get_similar_floors(
         ensure_default_cover_image(
             project_repository,
             project,
-            state.s3_client.as_ref(),
-            state.s3_bucket.as_deref(),
+            state.image_store.as_ref(),
             &state.cdn_base_url,
         )
         .await?;
@@ -293,7 +453,7 @@ pub async fn get_similar_floors(
     let mut responses = Vec::with_capacity(similar_floors.len());
     for record in similar_floors {
         if let Some(project) = project_map.get(&record.project_id) {
-            let response = FloorResponse::try_from_project(
+            let mut response = FloorResponse::try_from_project(
                 project,
                 &record.id,
                 &record.title,
@@ -302,6 +462,9 @@ pub async fn get_similar_floors(
                 &image_map,
             )
             .map_err(ApiError::internal)?;
+            if query.explain {
+                response.explanation = Some(SimilarityExplanation::from_floor(floor, &record));
+            }
             responses.push(response);
         }
     }
@@ -317,46 +480,93 @@ pub async fn get_similar_floors(
     }
     responses_with_images.extend(responses_without_images);
 
-    Ok(Json(responses_with_images))
+    Ok(PageResponse::new(responses_with_images, next_cursor))
 }
 
 pub async fn get_similar_rooms(
     State(state): State<AppState>,
     Path(room_id): Path<String>,
-    Query(query): Query<AreaRangeQuery>,
-) -> Result<Json<Vec<RoomResponse>>, ApiError> {
-    let project_repository = state.project_repository()?;
-    let room_structure_repository = state.room_structure_repository()?;
-    let image_repository = state.image_repository()?;
-
-    let room = room_structure_repository
+    Query(query): Query<SimilarQuery>,
+) -> Result<Json<PageResponse<RoomResponse>>, ApiError> {
+    let room = state
+        .room_structure_repository()
         .find_by_id(&room_id)
         .await?
         .ok_or_else(|| ApiError::not_found(format!("room {room_id} not found")))?;
 
+    let response = rank_similar_rooms(&state, &query, &room).await?;
+    Ok(Json(response))
+}
+
+/// Rank indexed rooms against a sketched outline that is not part of any project.
+pub async fn search_similar_rooms(
+    State(state): State<AppState>,
+    Query(query): Query<SimilarQuery>,
+    Json(request): Json<SimilarRoomRequest>,
+) -> Result<Json<PageResponse<RoomResponse>>, ApiError> {
+    let record = build_outline_room_structure_record(&request)?;
+    let room = room_structure::Model::from(record);
+
+    let response = rank_similar_rooms(&state, &query, &room).await?;
+    Ok(Json(response))
+}
+
+async fn rank_similar_rooms(
+    state: &AppState,
+    query: &SimilarQuery,
+    room: &room_structure::Model,
+) -> Result<PageResponse<RoomResponse>, ApiError> {
+    let limit = query.limit();
+    let scoring = &state.scoring_profile(query.profile.as_deref())?.room;
+
+    let project_repository = state.project_repository()?;
+    let room_structure_repository = state.room_structure_repository();
+    let image_repository = state.image_repository()?;
+
     let area_from = query
         .area_from
         .map(|value| (value as f64) * 1_000_000.0)
-        .unwrap_or(room.area * 0.85);
+        .unwrap_or(room.area * (1.0 - scoring.area_tolerance));
     let area_to = query
         .area_to
         .map(|value| (value as f64) * 1_000_000.0)
-        .unwrap_or(room.area * 1.15);
+        .unwrap_or(room.area * (1.0 + scoring.area_tolerance));
+    let ranking =
+        SimilarityCursor::fingerprint(&format!("{room:?}|{scoring:?}|{area_from}|{area_to}"));
+    let cursor = query.cursor(ranking)?;
 
-    let similar_rooms = room_structure_repository
+    let mut similar_rooms = room_structure_repository
         .find_similar_rooms(
             &room.project_id,
-            room.area,
+            room.polygon_area.unwrap_or(room.area),
             area_from,
             area_to,
             room.rectangularity,
             room.bounding_box_aspect,
+            room.compactness,
+            room.convexity,
+            room.shape_descriptor.as_deref(),
             room.r#type,
+            scoring,
+            limit + 1,
+            cursor.as_ref(),
         )
         .await?;
 
+    let next_cursor = if similar_rooms.len() as u64 > limit {
+        similar_rooms.truncate(limit as usize);
+        let offset = cursor.as_ref().map_or(0, |cursor| cursor.offset) + limit;
+        similar_rooms.last().map(|record| {
+            SimilarityCursor::new(record.score, &record.id, offset)
+                .with_ranking(ranking)
+                .encode()
+        })
+    } else {
+        None
+    };
+
     if similar_rooms.is_empty() {
-        return Ok(Json(Vec::new()));
+        return Ok(PageResponse::new(Vec::new(), next_cursor));
     }
 
     let mut project_ids: Vec<String> = similar_rooms
@@ -371,8 +581,7 @@ pub async fn get_similar_rooms(
         ensure_default_cover_image(
             project_repository,
             project,
-            state.s3_client.as_ref(),
-            state.s3_bucket.as_deref(),
+            state.image_store.as_ref(),
             &state.cdn_base_url,
         )
         .await?;
@@ -403,7 +612,7 @@ pub async fn get_similar_rooms(
     let mut responses = Vec::with_capacity(similar_rooms.len());
     for record in similar_rooms {
         if let Some(project) = project_map.get(&record.project_id) {
-            let response = RoomResponse::try_from_project(
+            let mut response = RoomResponse::try_from_project(
                 project,
                 &record.id,
                 &state.cdn_base_url,
@@ -411,6 +620,9 @@ pub async fn get_similar_rooms(
                 &image_map,
             )
             .map_err(ApiError::internal)?;
+            if query.explain {
+                response.explanation = Some(SimilarityExplanation::from_room(room, &record));
+            }
             responses.push(response);
         }
     }
@@ -426,57 +638,13 @@ pub async fn get_similar_rooms(
     }
     responses_with_images.extend(responses_without_images);
 
-    Ok(Json(responses_with_images))
-}
-
-async fn populate_floorplans(
-    http_client: &HttpClient,
-    cdn_base_url: &str,
-    project: &mut Project,
-) -> Result<(), ApiError> {
-    let Some(path) = project.floorplan_path.as_ref() else {
-        return Ok(());
-    };
-    if path.is_empty() {
-        return Ok(());
-    }
-
-    let Some(key) = project.floorplan_key() else {
-        return Ok(());
-    };
-
-    let url = format!(
-        "{}/{}",
-        cdn_base_url.trim_end_matches('/'),
-        key.trim_start_matches('/')
-    );
-    let response = http_client
-        .get(&url)
-        .send()
-        .await
-        .map_err(ApiError::internal)?;
-
-    if response.status() == HttpStatusCode::NOT_FOUND {
-        return Ok(());
-    }
-    if !response.status().is_success() {
-        return Err(ApiError::internal(anyhow::anyhow!(
-            "failed to fetch floorplans from {url}: status {}",
-            response.status()
-        )));
-    }
-
-    let payload = response.text().await.map_err(ApiError::internal)?;
-    let floorplans: Vec<Floorplan> = serde_json::from_str(&payload).map_err(ApiError::internal)?;
-    project.floorplans = floorplans;
-    Ok(())
+    Ok(PageResponse::new(responses_with_images, next_cursor))
 }
 
 async fn ensure_default_cover_image(
-    repository: &ProjectRepository,
+    repository: &dyn ProjectStore,
     project: &mut Project,
-    s3_client: Option<&S3Client>,
-    s3_bucket: Option<&str>,
+    object_store: &dyn ObjectStore,
     cdn_base_url: &str,
 ) -> Result<(), ApiError> {
     if project.default_cover_image.is_some() {
@@ -488,34 +656,31 @@ async fn ensure_default_cover_image(
         .clone()
         .ok_or_else(|| ApiError::internal(anyhow::anyhow!("project missing cover_image")))?;
 
-    if let (Some(client), Some(bucket), Some(project_id)) =
-        (s3_client, s3_bucket, project.id.clone())
-    {
+    if let Some(project_id) = project.id.clone() {
         let prefix = format!("projects/{project_id}/images");
-        let response = client
-            .list_objects_v2()
-            .bucket(bucket)
-            .prefix(&prefix)
-            .send()
-            .await
-            .map_err(ApiError::internal)?;
+        let contents = match object_store.list(&prefix).await {
+            Ok(contents) => contents,
+            // Without a listing the latest rendering is unknown, so the fallback is served
+            // but not persisted as the default.
+            Err(ObjectStoreError::Unsupported(err)) => {
+                tracing::debug!(project_id, "cannot list cover images: {err}");
+                project.default_cover_image = Some(cover_image);
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
 
-        let contents = response.contents();
         if !contents.is_empty() {
             let mut latest_key = None;
             let mut latest_time = None;
 
             for object in contents {
-                let key = object.key().map(ToOwned::to_owned);
-                let modified = object.last_modified().cloned();
-                match (key, modified) {
-                    (Some(key), Some(modified)) => {
-                        if latest_time.map(|t| t < modified).unwrap_or(true) {
-                            latest_time = Some(modified);
-                            latest_key = Some(key);
-                        }
-                    }
-                    _ => continue,
+                let Some(modified) = object.last_modified else {
+                    continue;
+                };
+                if latest_time.map(|t| t < modified).unwrap_or(true) {
+                    latest_time = Some(modified);
+                    latest_key = Some(object.key);
                 }
             }
 
@@ -551,7 +716,7 @@ async fn ensure_default_cover_image(
 fn build_structure_records(
     project_id: &str,
     floorplans: &[Floorplan],
-) -> Result<(Vec<FloorStructureRecord>, Vec<RoomStructureRecord>), ApiError> {
+) -> Result<(Vec<FloorStructureRecord>, Vec<RoomStructureRecord>), IndexFailure> {
     let floor_records = build_floor_structure_records(project_id, floorplans)?;
     let room_records = build_room_structure_records(project_id, floorplans)?;
 
@@ -561,26 +726,28 @@ fn build_structure_records(
 fn build_floor_structure_records(
     project_id: &str,
     floorplans: &[Floorplan],
-) -> Result<Vec<FloorStructureRecord>, ApiError> {
+) -> Result<Vec<FloorStructureRecord>, IndexFailure> {
     let mut records = Vec::with_capacity(floorplans.len());
 
     for floorplan in floorplans {
-        let bounding_box = BoundingBox::from_floorplan(floorplan).map_err(ApiError::internal)?;
         let area = floorplan
             .area
-            .ok_or_else(|| anyhow::anyhow!("floorplan {} missing area", floorplan.id))
-            .map_err(ApiError::internal)?;
+            .ok_or_else(|| missing_field(floorplan, "area"))?;
         let title = floorplan
             .title
             .clone()
-            .ok_or_else(|| anyhow::anyhow!("floorplan {} missing title", floorplan.id))
-            .map_err(ApiError::internal)?;
+            .ok_or_else(|| missing_field(floorplan, "title"))?;
         let room_count = floorplan
             .rooms
             .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("floorplan {} missing rooms", floorplan.id))
-            .map_err(ApiError::internal)?
+            .ok_or_else(|| missing_field(floorplan, "rooms"))?
             .len() as i32;
+        let bounding_box = BoundingBox::from_floorplan(floorplan).map_err(|err| {
+            IndexFailure::new(
+                IndexFailureReason::BadGeometry,
+                format!("floorplan {}: {err}", floorplan.id),
+            )
+        })?;
         let archi_id = floorplan.archi_id.clone();
         let rectangularity = if bounding_box.area > 0.0 {
             area * 1_000_000.0 / bounding_box.area
@@ -599,6 +766,7 @@ fn build_floor_structure_records(
             bounding_box_area: bounding_box.area,
             bounding_box_aspect: bounding_box.aspect,
             rectangularity,
+            graph_signature: Some(RoomGraph::from_floorplan(floorplan).signature()),
         });
     }
 
@@ -608,25 +776,37 @@ fn build_floor_structure_records(
 fn build_room_structure_records(
     project_id: &str,
     floorplans: &[Floorplan],
-) -> Result<Vec<RoomStructureRecord>, ApiError> {
+) -> Result<Vec<RoomStructureRecord>, IndexFailure> {
     let mut records = Vec::new();
 
     for floorplan in floorplans {
         let rooms = floorplan
             .rooms
             .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("floorplan {} missing rooms", floorplan.id))
-            .map_err(ApiError::internal)?;
+            .ok_or_else(|| missing_field(floorplan, "rooms"))?;
 
         for room in rooms {
-            let bounding_box =
-                BoundingBox::from_room(floorplan, room).map_err(ApiError::internal)?;
+            let bounding_box = BoundingBox::from_room(floorplan, room).map_err(|err| {
+                IndexFailure::new(
+                    IndexFailureReason::BadGeometry,
+                    format!(
+                        "room {} of floorplan {}: {err}",
+                        room.archi_id, floorplan.id
+                    ),
+                )
+            })?;
             let rectangularity = if bounding_box.area > 0.0 {
                 room.area / bounding_box.area
             } else {
                 0.0
             };
 
+            let shape = RoomShape::from_room(floorplan, room)
+                .inspect_err(
+                    |err| tracing::warn!(project_id, error = %err, "skipping room shape metrics"),
+                )
+                .ok();
+
             let archi_id = room.archi_id.clone();
 
             records.push(RoomStructureRecord {
@@ -639,9 +819,69 @@ fn build_room_structure_records(
                 bounding_box_area: bounding_box.area,
                 bounding_box_aspect: bounding_box.aspect,
                 rectangularity,
+                polygon_area: shape.as_ref().map(|shape| shape.area),
+                perimeter: shape.as_ref().map(|shape| shape.perimeter),
+                convexity: shape.as_ref().map(|shape| shape.convexity),
+                compactness: shape.as_ref().map(|shape| shape.compactness),
+                shape_descriptor: shape.and_then(|shape| shape.descriptor),
             });
         }
     }
 
     Ok(records)
 }
+
+fn missing_field(floorplan: &Floorplan, field: &str) -> IndexFailure {
+    IndexFailure::new(
+        IndexFailureReason::MissingField,
+        format!("floorplan {} missing {field}", floorplan.id),
+    )
+}
+
+/// Derive the same features as [`build_room_structure_records`] from a bare outline.
+fn build_outline_room_structure_record(
+    request: &SimilarRoomRequest,
+) -> Result<RoomStructureRecord, ApiError> {
+    let polygon = Polygon::new(
+        request
+            .outline
+            .iter()
+            .map(|point| Point2::new(point.x, point.z))
+            .collect(),
+    );
+    if polygon.is_degenerate() {
+        return Err(ApiError::unprocessable("room outline is degenerate"));
+    }
+
+    let bounding_box = BoundingBox::from_polygon(&polygon).map_err(ApiError::internal)?;
+    let shape = RoomShape::from_polygon(&polygon);
+    let area = request
+        .area
+        .map(|value| value * 1_000_000.0)
+        .unwrap_or(shape.area);
+    let rectangularity = if bounding_box.area > 0.0 {
+        area / bounding_box.area
+    } else {
+        0.0
+    };
+
+    Ok(RoomStructureRecord {
+        id: String::new(),
+        project_id: String::new(),
+        r#type: request.r#type.unwrap_or_default(),
+        area,
+        bounding_box_width: bounding_box.width,
+        bounding_box_depth: bounding_box.height,
+        bounding_box_area: bounding_box.area,
+        bounding_box_aspect: bounding_box.aspect,
+        rectangularity,
+        polygon_area: Some(shape.area),
+        perimeter: Some(shape.perimeter),
+        convexity: Some(shape.convexity),
+        compactness: Some(shape.compactness),
+        shape_descriptor: shape.descriptor,
+    })
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,240 @@
+//! Golden files for the floorplan documents under `tests/fixtures/floorplans`.
+//!
+//! Each `{name}.json` fixture has a `{name}.golden.json` next to it holding the document as we
+//! re-serialize it, the bounding boxes and the structure records built from it. Run with
+//! `UPDATE_GOLDEN=1` to rewrite the golden files after an intended change.
+//!
+//! Besides the clean documents, `studio_duplicate_corners` and `house_open_walls` are
+//! anonymized editor exports with the quirks real projects have: repeated corners, walls that
+//! do not close, rooms without an outline and items outside every room.
+//!
+//! The same fixtures check that an outline search derives the features indexing stored.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{Value, json};
+
+use super::{
+    build_floor_structure_records, build_outline_room_structure_record,
+    build_room_structure_records,
+};
+use crate::{
+    models::project::{
+        child::{
+            floorplan::Floorplan,
+            structure::{BoundingBox, room_polygon},
+        },
+        structure::{floor_structure, room_structure},
+    },
+    routes::project::dto::request::{OutlinePoint, SimilarRoomRequest},
+};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/floorplans");
+const GOLDEN_SUFFIX: &str = ".golden.json";
+/// Relative tolerance for floats, so libm differences between platforms do not fail the build.
+const TOLERANCE: f64 = 1e-9;
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(FIXTURES)
+        .expect("fixtures directory")
+        .map(|entry| entry.expect("fixture entry").path())
+        .filter(|path| {
+            let name = path.to_string_lossy();
+            name.ends_with(".json") && !name.ends_with(GOLDEN_SUFFIX)
+        })
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no fixtures in {FIXTURES}");
+    paths
+}
+
+fn golden_path(fixture: &Path) -> PathBuf {
+    let stem = fixture.file_stem().expect("fixture name").to_string_lossy();
+    fixture.with_file_name(format!("{stem}{GOLDEN_SUFFIX}"))
+}
+
+fn load(fixture: &Path) -> Vec<Floorplan> {
+    let payload = std::fs::read(fixture).expect("readable fixture");
+    serde_json::from_slice(&payload)
+        .unwrap_or_else(|err| panic!("{} no longer deserializes: {err}", fixture.display()))
+}
+
+fn snapshot(floorplans: &[Floorplan]) -> Value {
+    let mut floor_boxes = BTreeMap::new();
+    let mut room_boxes = BTreeMap::new();
+    for floorplan in floorplans {
+        floor_boxes.insert(
+            floorplan.archi_id.clone(),
+            outcome(BoundingBox::from_floorplan(floorplan)),
+        );
+        for room in floorplan.rooms.iter().flatten() {
+            room_boxes.insert(
+                room.archi_id.clone(),
+                outcome(BoundingBox::from_room(floorplan, room)),
+            );
+        }
+    }
+
+    let floors = build_floor_structure_records("project", floorplans).map(|records| {
+        records
+            .into_iter()
+            .map(floor_structure::Model::from)
+            .collect::<Vec<_>>()
+    });
+    let rooms = build_room_structure_records("project", floorplans).map(|records| {
+        records
+            .into_iter()
+            .map(room_structure::Model::from)
+            .collect::<Vec<_>>()
+    });
+
+    json!({
+        "document": floorplans,
+        "boundingBoxes": { "floors": floor_boxes, "rooms": room_boxes },
+        "floorRecords": outcome(floors),
+        "roomRecords": outcome(rooms),
+    })
+}
+
+fn outcome<T: serde::Serialize, E: std::fmt::Display>(result: Result<T, E>) -> Value {
+    match result {
+        Ok(value) => serde_json::to_value(value).expect("serializable"),
+        Err(err) => json!({ "error": err.to_string() }),
+    }
+}
+
+/// First path at which `actual` and `expected` differ, if any.
+fn mismatch(path: &str, actual: &Value, expected: &Value) -> Option<String> {
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(e)) => {
+            let (a, e) = (a.as_f64()?, e.as_f64()?);
+            let scale = a.abs().max(e.abs()).max(1.0);
+            ((a - e).abs() > TOLERANCE * scale).then(|| format!("{path}: {a} != {e}"))
+        }
+        (Value::Array(a), Value::Array(e)) => {
+            if a.len() != e.len() {
+                return Some(format!("{path}: {} items != {}", a.len(), e.len()));
+            }
+            a.iter()
+                .zip(e)
+                .enumerate()
+                .find_map(|(i, (a, e))| mismatch(&format!("{path}[{i}]"), a, e))
+        }
+        (Value::Object(a), Value::Object(e)) => {
+            let keys: std::collections::BTreeSet<_> = a.keys().chain(e.keys()).collect();
+            keys.into_iter().find_map(|key| {
+                let path = format!("{path}.{key}");
+                match (a.get(key), e.get(key)) {
+                    (Some(a), Some(e)) => mismatch(&path, a, e),
+                    (Some(_), None) => Some(format!("{path}: unexpected field")),
+                    (None, _) => Some(format!("{path}: missing field")),
+                }
+            })
+        }
+        _ => (actual != expected).then(|| format!("{path}: {actual} != {expected}")),
+    }
+}
+
+#[test]
+fn floorplans_round_trip() {
+    for fixture in fixtures() {
+        let floorplans = load(&fixture);
+        let serialized = serde_json::to_value(&floorplans).expect("serializable");
+        let reparsed: Vec<Floorplan> = serde_json::from_value(serialized).expect("reparsable");
+        assert_eq!(reparsed, floorplans, "{}", fixture.display());
+    }
+}
+
+#[test]
+fn floorplans_match_golden_files() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+
+    for fixture in fixtures() {
+        let actual = snapshot(&load(&fixture));
+        let golden = golden_path(&fixture);
+
+        if update {
+            let mut payload = serde_json::to_string_pretty(&actual).expect("serializable");
+            payload.push('\n');
+            std::fs::write(&golden, payload).expect("writable golden file");
+            continue;
+        }
+
+        let Ok(payload) = std::fs::read(&golden) else {
+            failures.push(format!(
+                "{} is missing; run with UPDATE_GOLDEN=1",
+                golden.display()
+            ));
+            continue;
+        };
+        let expected: Value = serde_json::from_slice(&payload).expect("golden JSON");
+        if let Some(diff) = mismatch("$", &actual, &expected) {
+            failures.push(format!("{}: {diff}", golden.display()));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn outline_of_an_indexed_room_reproduces_its_features() {
+    let mut checked = 0;
+    for fixture in fixtures() {
+        let floorplans = load(&fixture);
+        let Ok(records) = build_room_structure_records("project", &floorplans) else {
+            continue;
+        };
+
+        for (floorplan, room) in floorplans.iter().flat_map(|floorplan| {
+            floorplan
+                .rooms
+                .iter()
+                .flatten()
+                .map(move |room| (floorplan, room))
+        }) {
+            let Ok(polygon) = room_polygon(floorplan, room) else {
+                continue;
+            };
+            if room.area <= 0.0 {
+                continue;
+            }
+            let indexed = records
+                .iter()
+                .find(|record| record.id == format!("project_{}", room.archi_id))
+                .expect("indexed room");
+
+            let request = SimilarRoomRequest {
+                outline: polygon
+                    .points
+                    .iter()
+                    .map(|point| OutlinePoint {
+                        x: point.x,
+                        z: point.y,
+                    })
+                    .collect(),
+                r#type: Some(room.r#type),
+                area: None,
+            };
+            let searched = build_outline_room_structure_record(&request).expect("outline record");
+
+            let mut indexed = serde_json::to_value(room_structure::Model::from(indexed.clone()))
+                .expect("serializable");
+            let mut searched =
+                serde_json::to_value(room_structure::Model::from(searched)).expect("serializable");
+            for key in ["id", "project_id"] {
+                indexed.as_object_mut().expect("record").remove(key);
+                searched.as_object_mut().expect("record").remove(key);
+            }
+            if let Some(diff) = mismatch(&room.archi_id, &searched, &indexed) {
+                panic!("{}: {diff}", fixture.display());
+            }
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0, "no fixture room has an outline and an area");
+}
@@ -1,20 +1,31 @@
 mod config;
 mod error;
+mod geometry;
 mod handlers;
 mod models;
 mod repositories;
 mod routes;
+mod scoring;
 mod state;
 mod utils;
+mod workers;
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 use aws_config::{BehaviorVersion, meta::region::RegionProviderChain};
 use aws_sdk_s3::Client as S3Client;
 use aws_types::region::Region;
 use axum::Router;
+use config::{AppConfig, ObjectStoreConfig};
 use migration::{Migrator, MigratorTrait};
+use models::job::JobKind;
 use mongodb::Client as MongoClient;
+use repositories::{
+    image_repository::ImageStore,
+    object_store::{HttpObjectStore, LocalObjectStore, ObjectStore, S3ObjectStore},
+    project_repository::ProjectStore,
+    structure_store::{FloorStructureStore, RoomStructureStore},
+};
 use reqwest::Client as HttpClient;
 use routes::app_router;
 use sea_orm::Database;
@@ -55,44 +66,120 @@ async fn main() -> anyhow::Result<()> {
         None
     };
 
-    let s3_client = if config.s3_bucket_name.is_some() {
-        let region_provider = if let Some(region) = &config.aws_region {
-            RegionProviderChain::first_try(Region::new(region.clone())).or_default_provider()
-        } else {
-            RegionProviderChain::default_provider()
-        };
-        let aws_conf = aws_config::defaults(BehaviorVersion::latest())
-            .region(region_provider)
-            .load()
-            .await;
-        Some(S3Client::new(&aws_conf))
-    } else {
-        None
+    let object_store = open_object_store(&config, &config.object_store).await;
+    let image_store = match &config.image_store {
+        Some(image_store) => open_object_store(&config, image_store).await,
+        None => object_store.clone(),
     };
 
-    let project_repository = mongo
+    let mongo_projects = mongo
         .as_ref()
         .map(repositories::project_repository::ProjectRepository::new);
-    let image_repository = mongo
+    let mut project_repository: Option<Arc<dyn ProjectStore>> = mongo_projects
+        .clone()
+        .map(|repository| Arc::new(repository) as _);
+    let mut image_repository: Option<Arc<dyn ImageStore>> = mongo
         .as_ref()
-        .map(repositories::image_repository::ImageRepository::new);
-    let floor_structure_repository = postgres
+        .map(|db| Arc::new(repositories::image_repository::ImageRepository::new(db)) as _);
+    if let (None, ObjectStoreConfig::Local { root }) = (&mongo, &config.object_store) {
+        let fixtures = repositories::fixture_loader::load_fixtures(root)?;
+        tracing::info!(
+            "Mongo is not configured; serving projects from {}",
+            root.display()
+        );
+        project_repository = Some(Arc::new(fixtures.projects));
+        image_repository = Some(Arc::new(fixtures.images));
+    }
+    let (floor_structure_repository, room_structure_repository, needs_index_build): (
+        Arc<dyn FloorStructureStore>,
+        Arc<dyn RoomStructureStore>,
+        bool,
+    ) = match &postgres {
+        Some(db) => (
+            Arc::new(
+                repositories::floor_structure_repository::FloorStructureRepository::new(db.clone())
+                    .with_ann_candidates(config.ann_candidates)
+                    .with_chunk_size(config.upsert_chunk_size),
+            ),
+            Arc::new(
+                repositories::room_structure_repository::RoomStructureRepository::new(db.clone())
+                    .with_ann_candidates(config.ann_candidates)
+                    .with_chunk_size(config.upsert_chunk_size),
+            ),
+            false,
+        ),
+        None => {
+            let index = Arc::new(repositories::structure_index::StructureIndex::open(
+                config.structure_index_snapshot.clone(),
+            )?);
+            tracing::info!("Postgres is not configured; using the in-process structure index");
+            let needs_index_build = index.is_empty();
+            (index.clone(), index, needs_index_build)
+        }
+    };
+    let index_watermark_repository = postgres
+        .clone()
+        .map(repositories::index_watermark_repository::IndexWatermarkRepository::new);
+    let change_stream_token_repository = postgres
+        .clone()
+        .map(repositories::change_stream_token_repository::ChangeStreamTokenRepository::new);
+    let job_repository = postgres
+        .clone()
+        .map(repositories::job_repository::JobRepository::new);
+    let structure_index_error_repository = postgres
         .clone()
-        .map(repositories::floor_structure_repository::FloorStructureRepository::new);
-    let room_structure_repository =
-        postgres.map(repositories::room_structure_repository::RoomStructureRepository::new);
-    let http_client = HttpClient::new();
+        .map(repositories::structure_index_error_repository::StructureIndexErrorRepository::new);
+    let floorplan_fetcher = repositories::floorplan_fetcher::FloorplanFetcher::new(
+        object_store.clone(),
+        config.floorplan_fetch.clone(),
+    );
+
+    let mut dependency_probe =
+        repositories::dependency_probe::DependencyProbe::new(object_store.clone())
+            .with_mongo(mongo.clone())
+            .with_postgres(postgres.clone())
+            .with_timeout(config.readiness_timeout);
+    // When floorplans come from the CDN, the object store probe already covers it.
+    if config.cdn_url_set && !matches!(config.object_store, ObjectStoreConfig::Http) {
+        dependency_probe = dependency_probe.with_cdn(HttpClient::new(), config.cdn_url.clone());
+    }
 
     let state: AppState = AppState {
         project_repository,
         image_repository,
         floor_structure_repository,
         room_structure_repository,
-        s3_client,
-        s3_bucket: config.s3_bucket_name.clone(),
+        index_watermark_repository,
+        change_stream_token_repository,
+        job_repository,
+        structure_index_error_repository,
+        in_memory_jobs: Arc::default(),
+        in_memory_watermarks: Arc::default(),
+        image_store,
         cdn_base_url: config.cdn_url.clone(),
-        http_client,
+        floorplan_fetcher,
+        dependency_probe,
+        scoring_profiles: Arc::new(config.scoring_profiles),
+        index_concurrency: config.index_concurrency,
     };
+    if needs_index_build && state.project_repository.is_some() {
+        tracing::info!("building in-process structure index");
+        workers::structure_jobs::enqueue(&state, JobKind::RecentIndex).await?;
+    }
+    let resumed = workers::structure_jobs::resume_unfinished(&state).await?;
+    if resumed > 0 {
+        tracing::info!(resumed, "resumed unfinished indexing jobs");
+    }
+    if state.job_repository.is_some() {
+        tokio::spawn(workers::structure_jobs::reclaim_expired(state.clone()));
+    }
+
+    if config.project_change_stream
+        && let Some(projects) = mongo_projects
+    {
+        tokio::spawn(workers::project_changes::run(state.clone(), projects));
+    }
+
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
@@ -106,3 +193,28 @@ async fn main() -> anyhow::Result<()> {
     axum::serve(listener, router).await?;
     Ok(())
 }
+
+async fn open_object_store(config: &AppConfig, store: &ObjectStoreConfig) -> Arc<dyn ObjectStore> {
+    match store {
+        ObjectStoreConfig::S3 { bucket } => {
+            let region_provider = if let Some(region) = &config.aws_region {
+                RegionProviderChain::first_try(Region::new(region.clone())).or_default_provider()
+            } else {
+                RegionProviderChain::default_provider()
+            };
+            let aws_conf = aws_config::defaults(BehaviorVersion::latest())
+                .region(region_provider)
+                .load()
+                .await;
+            Arc::new(S3ObjectStore::new(S3Client::new(&aws_conf), bucket.clone()))
+        }
+        ObjectStoreConfig::Http => Arc::new(HttpObjectStore::new(
+            HttpClient::new(),
+            config.cdn_url.clone(),
+        )),
+        ObjectStoreConfig::Local { root } => {
+            tracing::info!("serving project assets from {}", root.display());
+            Arc::new(LocalObjectStore::new(root.clone()))
+        }
+    }
+}
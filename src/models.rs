@@ -2,5 +2,6 @@
 #![allow(unused_imports)]
 pub mod common;
 pub mod image;
+pub mod job;
 pub mod material;
 pub mod project;
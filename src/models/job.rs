@@ -0,0 +1,128 @@
+use chrono::Utc;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::project::structure::index_error::IndexFailureReason;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "camelCase")]
+pub enum JobKind {
+    /// Rebuild projects updated since the stored watermark.
+    #[sea_orm(string_value = "incremental_index")]
+    IncrementalIndex,
+    /// Rebuild the most recently updated projects.
+    #[sea_orm(string_value = "recent_index")]
+    RecentIndex,
+    /// Rebuild every project, e.g. after change events were missed.
+    #[sea_orm(string_value = "full_reindex")]
+    FullReindex,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    #[sea_orm(string_value = "queued")]
+    Queued,
+    #[sea_orm(string_value = "running")]
+    Running,
+    #[sea_orm(string_value = "succeeded")]
+    Succeeded,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+/// A project a job could not process; the job itself keeps going.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFailure {
+    pub project_id: String,
+    /// Absent from failures recorded before reasons were tracked.
+    #[serde(default = "unknown_failure_reason")]
+    pub reason: IndexFailureReason,
+    pub error: String,
+}
+
+fn unknown_failure_reason() -> IndexFailureReason {
+    IndexFailureReason::Internal
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "jobs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub total: i32,
+    pub processed: i32,
+    /// `Vec<JobFailure>` as JSON.
+    pub failures: Json,
+    /// Write counts accumulated so far, as JSON.
+    pub summary: Json,
+    pub error: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub started_at: Option<DateTimeUtc>,
+    pub finished_at: Option<DateTimeUtc>,
+    /// Instance processing the job; only written when the job is claimed.
+    #[serde(skip)]
+    pub owner: Option<String>,
+    /// Another instance may take the job over once this passes without a renewal.
+    #[serde(skip)]
+    pub lease_expires_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn new(kind: JobKind) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            kind,
+            status: JobStatus::Queued,
+            total: 0,
+            processed: 0,
+            failures: Json::Array(Vec::new()),
+            summary: Json::Null,
+            error: None,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+            owner: None,
+            lease_expires_at: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, JobStatus::Queued | JobStatus::Running)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.status, JobStatus::Succeeded | JobStatus::Failed)
+    }
+
+    /// Mark the job running; a job resumed after a restart starts its counters over.
+    pub fn start(&mut self) {
+        self.status = JobStatus::Running;
+        self.total = 0;
+        self.processed = 0;
+        self.failures = Json::Array(Vec::new());
+        self.summary = Json::Null;
+        self.error = None;
+        self.started_at = Some(Utc::now());
+        self.finished_at = None;
+    }
+
+    pub fn finish(&mut self, error: Option<String>) {
+        self.status = match error {
+            Some(_) => JobStatus::Failed,
+            None => JobStatus::Succeeded,
+        };
+        self.error = error;
+        self.finished_at = Some(Utc::now());
+    }
+}
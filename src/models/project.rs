@@ -2,6 +2,8 @@ pub mod child;
 pub mod enums;
 pub mod structure;
 
+use std::sync::Arc;
+
 use bson::DateTime as BsonDateTime;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -32,8 +34,9 @@ pub struct Project {
     pub default_cover_image: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub floorplan_path: Option<String>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub floorplans: Vec<Floorplan>,
+    /// Shared with the floorplan cache, so populating a project does not copy the documents.
+    #[serde(default, skip_serializing_if = "no_floorplans")]
+    pub floorplans: Arc<Vec<Floorplan>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub image_ids: Option<Vec<String>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -76,9 +79,12 @@ impl Project {
             .filter(|v| !v.is_empty());
     }
 
-    pub fn floorplan_key(&self) -> Option<String> {
-        self.id
-            .as_ref()
-            .map(|id| format!("projects/{}/floorplans.json", id))
+    /// Object store key of the floorplan documents of `project_id`.
+    pub fn floorplan_key(project_id: &str) -> String {
+        format!("projects/{project_id}/floorplans.json")
     }
 }
+
+fn no_floorplans(floorplans: &Arc<Vec<Floorplan>>) -> bool {
+    floorplans.is_empty()
+}
@@ -2,8 +2,10 @@ pub mod floorplan;
 pub mod from_map;
 pub mod portfolio;
 pub mod structure;
+pub mod validation;
 
 pub use floorplan::*;
 pub use from_map::FromMap;
 pub use portfolio::{Portfolio, Price};
-pub use structure::BoundingBox;
+pub use structure::{BoundingBox, RoomShape};
+pub use validation::{FloorplanIssue, FloorplanIssueKind, validate_floorplan};
@@ -1,3 +1,7 @@
 pub mod bounding_box;
+pub mod room_graph;
+pub mod room_shape;
 
 pub use bounding_box::BoundingBox;
+pub use room_graph::{GraphSignature, RoomGraph};
+pub use room_shape::{RoomShape, room_polygon};
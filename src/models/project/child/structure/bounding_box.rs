@@ -1,10 +1,18 @@
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
 
+use crate::geometry::{Point2, Polygon};
 use crate::models::common::Transformation;
 
 use crate::models::project::child::floorplan::{Floorplan, Room};
 
+/// Editor coordinates (corners, inner points) are stored at half scale; multiplying lengths by
+/// this factor gives the millimetres `room.area` is measured in.
+pub const EDITOR_SCALE: f64 = 2.0;
+
+/// Minimum-area oriented bounding box, so rotating or mirroring a shape leaves it unchanged.
+///
+/// `width` is the longer side and `height` the shorter one, which keeps `aspect >= 1`.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BoundingBox {
@@ -50,50 +58,36 @@ impl BoundingBox {
         let inner_points: Vec<&Transformation> = room.inner_points.iter().collect();
         compute_bounding_box(&inner_points)
     }
-}
 
-fn compute_bounding_box(points: &[&Transformation]) -> Result<BoundingBox> {
-    let xs: Vec<f64> = points.iter().filter_map(|p| p.x).collect();
-    let zs: Vec<f64> = points.iter().filter_map(|p| p.z).collect();
+    pub fn from_polygon(polygon: &Polygon) -> Result<Self> {
+        let rect = polygon
+            .minimum_area_rectangle()
+            .ok_or_else(|| anyhow!("Unable to compute bounding box without coordinates"))?;
+        // Collinear points leave no breadth, and an infinite aspect cannot be ranked or stored.
+        if !rect.aspect().is_finite() {
+            return Err(anyhow!("Bounding box is degenerate"));
+        }
+
+        let width = rect.length * EDITOR_SCALE;
+        let height = rect.breadth * EDITOR_SCALE;
 
-    if xs.is_empty() || zs.is_empty() {
-        return Err(anyhow!(
-            "Unable to compute bounding box without coordinates"
-        ));
+        Ok(BoundingBox {
+            width,
+            height,
+            area: width * height,
+            aspect: rect.aspect(),
+        })
     }
+}
 
-    let min_x = xs
-        .iter()
-        .cloned()
-        .fold(f64::INFINITY, |acc, value| acc.min(value));
-    let max_x = xs
+fn compute_bounding_box(points: &[&Transformation]) -> Result<BoundingBox> {
+    let points: Vec<Point2> = points
         .iter()
-        .cloned()
-        .fold(f64::NEG_INFINITY, |acc, value| acc.max(value));
+        .filter_map(|p| Some(Point2::new(p.x?, p.z?)))
+        .collect();
 
-    let min_z = zs
-        .iter()
-        .cloned()
-        .fold(f64::INFINITY, |acc, value| acc.min(value));
-    let max_z = zs
-        .iter()
-        .cloned()
-        .fold(f64::NEG_INFINITY, |acc, value| acc.max(value));
-
-    let width = (max_x - min_x) * 2.0;
-    let height = (max_z - min_z) * 2.0;
-    let area = width * height;
-
-    let aspect = if height != 0.0 {
-        width / height
-    } else {
-        f64::INFINITY
-    };
-
-    Ok(BoundingBox {
-        width,
-        height,
-        area,
-        aspect,
-    })
+    BoundingBox::from_polygon(&Polygon::new(points))
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,118 @@
+use std::f64::consts::FRAC_PI_4;
+
+use super::{BoundingBox, EDITOR_SCALE};
+use crate::geometry::{Point2, Polygon};
+
+/// 6×2 rectangle with a 2×1 notch cut into its top edge.
+fn notched() -> Vec<Point2> {
+    [
+        (0.0, 0.0),
+        (6.0, 0.0),
+        (6.0, 2.0),
+        (4.0, 2.0),
+        (4.0, 1.0),
+        (2.0, 1.0),
+        (2.0, 2.0),
+        (0.0, 2.0),
+    ]
+    .into_iter()
+    .map(|(x, y)| Point2::new(x, y))
+    .collect()
+}
+
+fn rotated(points: &[Point2], angle: f64) -> Polygon {
+    let (sin, cos) = angle.sin_cos();
+    Polygon::new(
+        points
+            .iter()
+            .map(|p| Point2::new(p.x * cos - p.y * sin + 10.0, p.x * sin + p.y * cos - 3.0))
+            .collect(),
+    )
+}
+
+fn mirrored(points: &[Point2]) -> Polygon {
+    Polygon::new(
+        points
+            .iter()
+            .rev()
+            .map(|p| Point2::new(-p.x, p.y))
+            .collect(),
+    )
+}
+
+fn bounding_box(polygon: &Polygon) -> BoundingBox {
+    BoundingBox::from_polygon(polygon).expect("non-empty polygon")
+}
+
+fn assert_same_box(actual: &BoundingBox, expected: &BoundingBox) {
+    for (name, actual, expected) in [
+        ("width", actual.width, expected.width),
+        ("height", actual.height, expected.height),
+        ("area", actual.area, expected.area),
+        ("aspect", actual.aspect, expected.aspect),
+    ] {
+        assert!(
+            (actual - expected).abs() < 1e-9 * expected.max(1.0),
+            "{name}: {actual} != {expected}"
+        );
+    }
+}
+
+#[test]
+fn rotation_leaves_the_box_unchanged() {
+    let upright = bounding_box(&Polygon::new(notched()));
+    assert_same_box(
+        &upright,
+        &BoundingBox {
+            width: 6.0 * EDITOR_SCALE,
+            height: 2.0 * EDITOR_SCALE,
+            area: 12.0 * EDITOR_SCALE * EDITOR_SCALE,
+            aspect: 3.0,
+        },
+    );
+
+    for degrees in [17.0_f64, 90.0, 135.0, 200.0, 333.0] {
+        assert_same_box(
+            &bounding_box(&rotated(&notched(), degrees.to_radians())),
+            &upright,
+        );
+    }
+}
+
+#[test]
+fn mirroring_leaves_the_box_unchanged() {
+    let upright = bounding_box(&Polygon::new(notched()));
+
+    assert_same_box(&bounding_box(&mirrored(&notched())), &upright);
+    let turned = rotated(&notched(), 0.4);
+    assert_same_box(&bounding_box(&mirrored(&turned.points)), &upright);
+}
+
+#[test]
+fn a_square_turned_by_45_degrees_keeps_its_own_size() {
+    let square = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)].map(|(x, y)| Point2::new(x, y));
+
+    // An axis-aligned box around the diamond would be twice the square's area.
+    let diamond = bounding_box(&rotated(&square, FRAC_PI_4));
+
+    assert_same_box(&diamond, &bounding_box(&Polygon::new(square.to_vec())));
+    assert!((diamond.aspect - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn the_longer_side_is_the_width() {
+    let tall = [(0.0, 0.0), (2.0, 0.0), (2.0, 5.0), (0.0, 5.0)].map(|(x, y)| Point2::new(x, y));
+
+    let bounding_box = bounding_box(&Polygon::new(tall.to_vec()));
+
+    assert_eq!(bounding_box.width, 5.0 * EDITOR_SCALE);
+    assert_eq!(bounding_box.height, 2.0 * EDITOR_SCALE);
+    assert_eq!(bounding_box.aspect, 2.5);
+}
+
+#[test]
+fn collinear_points_have_no_bounding_box() {
+    let line = [(0.0, 0.0), (2.0, 0.0), (5.0, 0.0)].map(|(x, y)| Point2::new(x, y));
+
+    assert!(BoundingBox::from_polygon(&Polygon::new(line.to_vec())).is_err());
+}
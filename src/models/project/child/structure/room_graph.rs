@@ -0,0 +1,180 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{models::project::child::floorplan::Floorplan, utils::hash::fnv1a};
+
+const WL_ITERATIONS: usize = 2;
+
+/// Rooms of a floorplan labelled by `Room::type`, connected when they share a wall segment.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RoomGraph {
+    pub labels: Vec<i32>,
+    pub edges: BTreeSet<(usize, usize)>,
+}
+
+/// Order-independent summary of a [`RoomGraph`] used to compare floor layouts.
+///
+/// Every histogram maps a label to its number of occurrences.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphSignature {
+    /// Rooms per room type.
+    pub room_types: BTreeMap<String, u32>,
+    /// Adjacencies per unordered room type pair, e.g. `"1-4"`.
+    pub adjacencies: BTreeMap<String, u32>,
+    /// Weisfeiler–Lehman subtree labels over all refinement iterations.
+    pub wl_labels: BTreeMap<String, u32>,
+}
+
+impl GraphSignature {
+    /// Mean weighted Jaccard distance to `other` over the three histograms.
+    ///
+    /// A missing signature is treated as empty histograms, which puts it at the maximum distance
+    /// from any non-empty signature.
+    pub fn distance(&self, other: Option<&GraphSignature>) -> f64 {
+        let empty = BTreeMap::new();
+        let pairs = [
+            (&self.room_types, other.map(|o| &o.room_types)),
+            (&self.adjacencies, other.map(|o| &o.adjacencies)),
+            (&self.wl_labels, other.map(|o| &o.wl_labels)),
+        ];
+
+        pairs
+            .iter()
+            .map(|(query, candidate)| histogram_distance(query, candidate.unwrap_or(&empty)))
+            .sum::<f64>()
+            / pairs.len() as f64
+    }
+}
+
+fn histogram_distance(a: &BTreeMap<String, u32>, b: &BTreeMap<String, u32>) -> f64 {
+    let (mut min_sum, mut max_sum) = (0.0, 0.0);
+    for key in a.keys().chain(b.keys().filter(|key| !a.contains_key(*key))) {
+        let x = f64::from(a.get(key).copied().unwrap_or_default());
+        let y = f64::from(b.get(key).copied().unwrap_or_default());
+        min_sum += x.min(y);
+        max_sum += x.max(y);
+    }
+
+    if max_sum == 0.0 {
+        0.0
+    } else {
+        1.0 - min_sum / max_sum
+    }
+}
+
+impl RoomGraph {
+    pub fn from_floorplan(floorplan: &Floorplan) -> Self {
+        let Some(rooms) = floorplan.rooms.as_ref() else {
+            return Self::default();
+        };
+
+        let mut wall_segments: BTreeSet<(&str, &str)> = BTreeSet::new();
+        for wall in floorplan.walls.iter().flatten() {
+            let Some(corners) = wall.corners.as_ref() else {
+                continue;
+            };
+            for pair in corners.windows(2) {
+                wall_segments.insert(segment_key(&pair[0], &pair[1]));
+            }
+        }
+
+        let mut rooms_by_segment: HashMap<(&str, &str), Vec<usize>> = HashMap::new();
+        for (index, room) in rooms.iter().enumerate() {
+            let n = room.corners.len();
+            if n < 2 {
+                continue;
+            }
+            for i in 0..n {
+                let key = segment_key(&room.corners[i], &room.corners[(i + 1) % n]);
+                if wall_segments.contains(&key) {
+                    rooms_by_segment.entry(key).or_default().push(index);
+                }
+            }
+        }
+
+        let mut edges = BTreeSet::new();
+        for members in rooms_by_segment.values() {
+            for (i, a) in members.iter().enumerate() {
+                for b in &members[i + 1..] {
+                    if a != b {
+                        edges.insert(((*a).min(*b), (*a).max(*b)));
+                    }
+                }
+            }
+        }
+
+        Self {
+            labels: rooms.iter().map(|room| room.r#type).collect(),
+            edges,
+        }
+    }
+
+    pub fn signature(&self) -> GraphSignature {
+        let mut room_types = BTreeMap::new();
+        for label in &self.labels {
+            *room_types.entry(label.to_string()).or_insert(0) += 1;
+        }
+
+        let mut adjacencies = BTreeMap::new();
+        for (a, b) in &self.edges {
+            let (x, y) = (self.labels[*a], self.labels[*b]);
+            let key = format!("{}-{}", x.min(y), x.max(y));
+            *adjacencies.entry(key).or_insert(0) += 1;
+        }
+
+        GraphSignature {
+            room_types,
+            adjacencies,
+            wl_labels: self.weisfeiler_lehman_labels(),
+        }
+    }
+
+    fn weisfeiler_lehman_labels(&self) -> BTreeMap<String, u32> {
+        let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); self.labels.len()];
+        for (a, b) in &self.edges {
+            neighbours[*a].push(*b);
+            neighbours[*b].push(*a);
+        }
+
+        let mut labels: Vec<u64> = self
+            .labels
+            .iter()
+            .map(|label| fnv1a(label.to_string().as_bytes()))
+            .collect();
+        let mut histogram = BTreeMap::new();
+        for label in &labels {
+            *histogram.entry(format!("{label:016x}")).or_insert(0) += 1;
+        }
+
+        for _ in 0..WL_ITERATIONS {
+            labels = neighbours
+                .iter()
+                .enumerate()
+                .map(|(node, adjacent)| {
+                    let mut neighbour_labels: Vec<u64> =
+                        adjacent.iter().map(|other| labels[*other]).collect();
+                    neighbour_labels.sort_unstable();
+                    let mut bytes = labels[node].to_le_bytes().to_vec();
+                    for label in neighbour_labels {
+                        bytes.extend_from_slice(&label.to_le_bytes());
+                    }
+                    fnv1a(&bytes)
+                })
+                .collect();
+            for label in &labels {
+                *histogram.entry(format!("{label:016x}")).or_insert(0) += 1;
+            }
+        }
+
+        histogram
+    }
+}
+
+fn segment_key<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,86 @@
+use serde_json::json;
+
+use super::{RoomGraph, fnv1a};
+use crate::models::project::child::floorplan::Floorplan;
+
+/// Kitchen (type 4) and living room (type 1) side by side; `walled` puts a wall on their
+/// shared side.
+fn two_rooms(walled: bool, kitchen_first: bool) -> Floorplan {
+    let kitchen = json!({ "corners": ["c0", "c1", "c2", "c3"], "type": 4 });
+    let living = json!({ "corners": ["c1", "c4", "c5", "c2"], "type": 1 });
+    let rooms = if kitchen_first {
+        [kitchen, living]
+    } else {
+        [living, kitchen]
+    };
+    let walls = if walled {
+        json!([{ "corners": ["c2", "c1"] }])
+    } else {
+        json!([{ "corners": ["c0", "c1"] }])
+    };
+
+    serde_json::from_value(json!({ "rooms": rooms, "walls": walls })).expect("floorplan")
+}
+
+#[test]
+fn rooms_sharing_a_wall_are_adjacent() {
+    let graph = RoomGraph::from_floorplan(&two_rooms(true, true));
+
+    assert_eq!(graph.labels, vec![4, 1]);
+    assert_eq!(
+        graph.edges.iter().copied().collect::<Vec<_>>(),
+        vec![(0, 1)]
+    );
+    assert!(
+        RoomGraph::from_floorplan(&two_rooms(false, true))
+            .edges
+            .is_empty()
+    );
+    assert_eq!(
+        RoomGraph::from_floorplan(&Floorplan::new()),
+        RoomGraph::default()
+    );
+}
+
+#[test]
+fn signature_counts_types_adjacencies_and_wl_labels() {
+    let signature = RoomGraph::from_floorplan(&two_rooms(true, true)).signature();
+
+    assert_eq!(
+        signature.room_types,
+        [("1".to_string(), 1), ("4".to_string(), 1)].into()
+    );
+    assert_eq!(signature.adjacencies, [("1-4".to_string(), 1)].into());
+    // Every room gets one label for its type and one per refinement iteration.
+    assert_eq!(
+        signature.wl_labels.values().sum::<u32>(),
+        2 * (1 + super::WL_ITERATIONS as u32)
+    );
+}
+
+#[test]
+fn signature_ignores_room_order() {
+    let forward = RoomGraph::from_floorplan(&two_rooms(true, true)).signature();
+    let reversed = RoomGraph::from_floorplan(&two_rooms(true, false)).signature();
+
+    assert_eq!(forward, reversed);
+    assert_eq!(forward.distance(Some(&reversed)), 0.0);
+}
+
+#[test]
+fn wl_labels_tell_apart_graphs_with_the_same_rooms() {
+    let walled = RoomGraph::from_floorplan(&two_rooms(true, true)).signature();
+    let unwalled = RoomGraph::from_floorplan(&two_rooms(false, true)).signature();
+
+    assert_eq!(walled.room_types, unwalled.room_types);
+    assert_ne!(walled.wl_labels, unwalled.wl_labels);
+    assert!(walled.distance(Some(&unwalled)) > 0.0);
+    assert_eq!(walled.distance(None), 1.0);
+}
+
+#[test]
+fn wl_hash_is_stable_fnv1a() {
+    // Persisted signatures depend on these values; changing the hash invalidates them.
+    assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+}
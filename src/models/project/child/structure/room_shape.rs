@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::{Point2, Polygon, fourier_descriptor};
+use crate::models::project::child::floorplan::{Floorplan, Room};
+use crate::models::project::child::structure::bounding_box::EDITOR_SCALE;
+
+/// Shape metrics of a room outline reconstructed from its corners.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomShape {
+    /// Enclosed area, scaled like `room.area`.
+    pub area: f64,
+    /// Outline length, scaled like the bounding box sides.
+    pub perimeter: f64,
+    pub convexity: f64,
+    pub compactness: f64,
+    /// Scale-, rotation- and mirror-invariant outline descriptor.
+    pub descriptor: Option<Vec<f64>>,
+}
+
+impl RoomShape {
+    pub fn from_room(floorplan: &Floorplan, room: &Room) -> Result<Self> {
+        let polygon = room_polygon(floorplan, room)?;
+        Ok(Self::from_polygon(&polygon))
+    }
+
+    pub fn from_polygon(polygon: &Polygon) -> Self {
+        Self {
+            area: polygon.area() * EDITOR_SCALE * EDITOR_SCALE,
+            perimeter: polygon.perimeter() * EDITOR_SCALE,
+            convexity: polygon.convexity(),
+            compactness: polygon.compactness(),
+            descriptor: fourier_descriptor(polygon),
+        }
+    }
+}
+
+/// Resolve `room.corners` against `floorplan.corners` into an x/z polygon.
+pub fn room_polygon(floorplan: &Floorplan, room: &Room) -> Result<Polygon> {
+    let corners = floorplan
+        .corners
+        .as_ref()
+        .ok_or_else(|| anyhow!("Floorplan corners are missing"))?;
+
+    let positions: HashMap<&str, Point2> = corners
+        .iter()
+        .filter_map(|corner| {
+            let id = corner.archi_id.as_deref()?;
+            let position = corner.position.as_ref()?;
+            Some((id, Point2::new(position.x?, position.z?)))
+        })
+        .collect();
+
+    let points = room
+        .corners
+        .iter()
+        .map(|corner_id| {
+            positions.get(corner_id.as_str()).copied().ok_or_else(|| {
+                anyhow!(
+                    "Room {} references unknown corner {corner_id}",
+                    room.archi_id
+                )
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let polygon = Polygon::new(points);
+    if polygon.is_degenerate() {
+        return Err(anyhow!("Room {} polygon is degenerate", room.archi_id));
+    }
+
+    Ok(polygon)
+}
@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::geometry::{Point2, Polygon};
+use crate::models::project::child::floorplan::{Floorplan, Room};
+
+/// Data problems that break indexing or rendering of a floorplan document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FloorplanIssueKind {
+    /// A floorplan whose `area` is zero, negative, non-finite or null.
+    MissingFloorplanArea,
+    /// A floorplan without a title, or with a blank one.
+    MissingTitle,
+    /// A floorplan whose `rooms` are missing or empty.
+    MissingRooms,
+    /// A room lists a corner id that is not in `floorplan.corners`.
+    UnknownCorner,
+    /// A wall with fewer than two corners.
+    IncompleteWall,
+    /// A room outline whose edges cross or touch each other.
+    SelfIntersectingRoom,
+    /// A room whose `area` is zero, negative or null.
+    MissingArea,
+    /// A room without `innerPoints`.
+    EmptyInnerPoints,
+    /// A floorplan item that no room lists among its items.
+    UnassignedItem,
+    /// An archi id shared by several corners, walls, rooms, items, points or lines.
+    DuplicateArchiId,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloorplanIssue {
+    pub kind: FloorplanIssueKind,
+    pub floorplan_id: String,
+    /// Archi id of the offending element, when it has one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element_id: Option<String>,
+    pub message: String,
+}
+
+/// Check one floorplan document, returning every issue found in document order.
+pub fn validate_floorplan(floorplan: &Floorplan) -> Vec<FloorplanIssue> {
+    let mut issues = Vec::new();
+    let mut report = |kind, element_id: Option<&str>, message: String| {
+        issues.push(FloorplanIssue {
+            kind,
+            floorplan_id: floorplan.id.clone(),
+            element_id: element_id.map(str::to_string),
+            message,
+        });
+    };
+
+    if !floorplan
+        .area
+        .is_some_and(|area| area.is_finite() && area > 0.0)
+    {
+        report(
+            FloorplanIssueKind::MissingFloorplanArea,
+            None,
+            format!("Floorplan {} has no area", floorplan.id),
+        );
+    }
+    if floorplan
+        .title
+        .as_deref()
+        .is_none_or(|title| title.trim().is_empty())
+    {
+        report(
+            FloorplanIssueKind::MissingTitle,
+            None,
+            format!("Floorplan {} has no title", floorplan.id),
+        );
+    }
+    if floorplan.rooms.as_ref().is_none_or(Vec::is_empty) {
+        report(
+            FloorplanIssueKind::MissingRooms,
+            None,
+            format!("Floorplan {} has no rooms", floorplan.id),
+        );
+    }
+
+    let corners: HashMap<&str, Option<Point2>> = floorplan
+        .corners
+        .iter()
+        .flatten()
+        .filter_map(|corner| {
+            let id = corner.archi_id.as_deref()?;
+            let position = corner
+                .position
+                .as_ref()
+                .and_then(|position| Some(Point2::new(position.x?, position.z?)));
+            Some((id, position))
+        })
+        .collect();
+
+    for wall in floorplan.walls.iter().flatten() {
+        let count = wall.corners.as_ref().map_or(0, Vec::len);
+        if count < 2 {
+            report(
+                FloorplanIssueKind::IncompleteWall,
+                wall.archi_id.as_deref(),
+                format!(
+                    "Wall {} has {count} corner(s)",
+                    wall.archi_id.as_deref().unwrap_or("<unnamed>")
+                ),
+            );
+        }
+    }
+
+    for room in floorplan.rooms.iter().flatten() {
+        let id = Some(room.archi_id.as_str());
+        let unknown: Vec<&str> = room
+            .corners
+            .iter()
+            .map(String::as_str)
+            .filter(|corner_id| !corners.contains_key(corner_id))
+            .collect();
+        for corner_id in &unknown {
+            report(
+                FloorplanIssueKind::UnknownCorner,
+                id,
+                format!(
+                    "Room {} references unknown corner {corner_id}",
+                    room.archi_id
+                ),
+            );
+        }
+        if unknown.is_empty()
+            && let Some(polygon) = outline(room, &corners)
+            && polygon.is_self_intersecting()
+        {
+            report(
+                FloorplanIssueKind::SelfIntersectingRoom,
+                id,
+                format!("Room {} outline intersects itself", room.archi_id),
+            );
+        }
+        if room.area.is_nan() || room.area <= 0.0 {
+            report(
+                FloorplanIssueKind::MissingArea,
+                id,
+                format!("Room {} has no area", room.archi_id),
+            );
+        }
+        if room.inner_points.is_empty() {
+            report(
+                FloorplanIssueKind::EmptyInnerPoints,
+                id,
+                format!("Room {} has no inner points", room.archi_id),
+            );
+        }
+    }
+
+    let assigned: HashSet<&str> = floorplan
+        .rooms
+        .iter()
+        .flatten()
+        .flat_map(|room| &room.items)
+        .filter_map(|item| item.archi_id.as_deref())
+        .collect();
+    for item in floorplan.items.iter().flatten() {
+        if let Some(id) = item.archi_id.as_deref()
+            && !assigned.contains(id)
+        {
+            report(
+                FloorplanIssueKind::UnassignedItem,
+                Some(id),
+                format!("Item {id} is not listed in any room"),
+            );
+        }
+    }
+
+    // Room items reference floorplan items by archi id, so they are not counted here.
+    let mut occurrences: BTreeMap<&str, usize> = BTreeMap::new();
+    let ids = floorplan
+        .corners
+        .iter()
+        .flatten()
+        .filter_map(|corner| corner.archi_id.as_deref())
+        .chain(
+            floorplan
+                .walls
+                .iter()
+                .flatten()
+                .filter_map(|wall| wall.archi_id.as_deref()),
+        )
+        .chain(
+            floorplan
+                .rooms
+                .iter()
+                .flatten()
+                .map(|room| room.archi_id.as_str()),
+        )
+        .chain(
+            floorplan
+                .items
+                .iter()
+                .flatten()
+                .filter_map(|item| item.archi_id.as_deref()),
+        )
+        .chain(
+            floorplan
+                .points
+                .iter()
+                .flatten()
+                .filter_map(|point| point.archi_id.as_deref()),
+        )
+        .chain(
+            floorplan
+                .lines
+                .iter()
+                .flatten()
+                .filter_map(|line| line.archi_id.as_deref()),
+        );
+    for id in ids {
+        *occurrences.entry(id).or_default() += 1;
+    }
+    for (id, count) in occurrences {
+        if count > 1 {
+            report(
+                FloorplanIssueKind::DuplicateArchiId,
+                Some(id),
+                format!("Archi id {id} is used by {count} elements"),
+            );
+        }
+    }
+
+    issues
+}
+
+/// Room outline when every corner resolves to a position.
+fn outline(room: &Room, corners: &HashMap<&str, Option<Point2>>) -> Option<Polygon> {
+    room.corners
+        .iter()
+        .map(|corner_id| corners.get(corner_id.as_str()).copied().flatten())
+        .collect::<Option<Vec<_>>>()
+        .map(Polygon::new)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,65 @@
+use super::{FloorplanIssueKind, validate_floorplan};
+use crate::models::project::child::floorplan::Floorplan;
+
+const CLEAN_FLOORPLANS: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/projects/project-a/floorplans.json"
+));
+
+/// A floorplan without issues, to break one field at a time.
+fn clean_floorplan() -> Floorplan {
+    let floorplans: Vec<Floorplan> =
+        serde_json::from_str(CLEAN_FLOORPLANS).expect("fixture floorplans parse");
+    let floorplan = floorplans.into_iter().next().expect("one floorplan");
+    assert_eq!(validate_floorplan(&floorplan), []);
+    floorplan
+}
+
+fn kinds(floorplan: &Floorplan) -> Vec<FloorplanIssueKind> {
+    validate_floorplan(floorplan)
+        .into_iter()
+        .map(|issue| issue.kind)
+        .collect()
+}
+
+#[test]
+fn reports_a_null_zero_or_non_finite_floorplan_area() {
+    for area in [
+        None,
+        Some(0.0),
+        Some(-4.0),
+        Some(f64::NAN),
+        Some(f64::INFINITY),
+    ] {
+        let mut floorplan = clean_floorplan();
+        floorplan.area = area;
+        assert_eq!(
+            kinds(&floorplan),
+            [FloorplanIssueKind::MissingFloorplanArea],
+            "area {area:?}"
+        );
+    }
+}
+
+#[test]
+fn reports_a_missing_or_blank_title() {
+    for title in [None, Some(String::new()), Some("  ".to_string())] {
+        let mut floorplan = clean_floorplan();
+        floorplan.title = title.clone();
+        let issues = validate_floorplan(&floorplan);
+        assert_eq!(issues.len(), 1, "title {title:?}");
+        assert_eq!(issues[0].kind, FloorplanIssueKind::MissingTitle);
+        assert_eq!(issues[0].element_id, None);
+    }
+}
+
+#[test]
+fn reports_missing_or_empty_rooms() {
+    for rooms in [None, Some(Vec::new())] {
+        let mut floorplan = clean_floorplan();
+        floorplan.rooms = rooms;
+        // Items were assigned to the removed rooms.
+        floorplan.items = None;
+        assert_eq!(kinds(&floorplan), [FloorplanIssueKind::MissingRooms]);
+    }
+}
@@ -1,4 +1,7 @@
+pub mod change_stream_token;
 pub mod floor_structure;
+pub mod index_error;
+pub mod index_watermark;
 pub mod room_structure;
 
 pub use floor_structure::{Column as FloorStructureColumn, Entity as FloorStructureEntity};
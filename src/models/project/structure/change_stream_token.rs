@@ -0,0 +1,15 @@
+use sea_orm::entity::prelude::*;
+
+/// Last processed resume token of a MongoDB change stream, BSON-encoded.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "change_stream_tokens")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub name: String,
+    pub token: Vec<u8>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,9 @@
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+use crate::models::project::child::structure::GraphSignature;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "floors")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -14,9 +17,22 @@ pub struct Model {
     pub bounding_box_area: f64,
     pub bounding_box_aspect: f64,
     pub rectangularity: f64,
+    pub graph_signature: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Stored graph signature; one that no longer deserializes is logged and treated as missing.
+    pub fn decode_graph_signature(&self) -> Option<GraphSignature> {
+        let value = self.graph_signature.clone()?;
+        serde_json::from_value(value)
+            .inspect_err(|err| {
+                tracing::warn!(floor_id = %self.id, error = %err, "unreadable graph signature");
+            })
+            .ok()
+    }
+}
@@ -0,0 +1,53 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Why a project could not be indexed.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(32))")]
+#[serde(rename_all = "camelCase")]
+pub enum IndexFailureReason {
+    /// A floorplan lacks `title`, `area` or `rooms`.
+    #[sea_orm(string_value = "missing_field")]
+    MissingField,
+    /// Rooms or floors without usable coordinates.
+    #[sea_orm(string_value = "bad_geometry")]
+    BadGeometry,
+    /// The CDN answered with a server error or could not be reached.
+    #[sea_orm(string_value = "upstream_error")]
+    UpstreamError,
+    /// `floorplans.json` is not valid JSON for [`Floorplan`](crate::models::project::child::Floorplan).
+    #[sea_orm(string_value = "parse_error")]
+    ParseError,
+    /// Anything else, e.g. a database failure while saving.
+    #[sea_orm(string_value = "internal")]
+    Internal,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "structure_index_errors")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub project_id: String,
+    pub job_id: Option<Uuid>,
+    pub reason: IndexFailureReason,
+    pub message: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+
+/// How far an incremental indexer has progressed through the projects collection.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "index_watermarks")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub name: String,
+    /// `None` until the first project has been passed.
+    pub updated_at: Option<DateTimeUtc>,
+    pub project_id: Option<String>,
+    /// Project right after the watermark that keeps failing transiently.
+    pub held_project_id: Option<String>,
+    /// Jobs in a row that stopped at `held_project_id`.
+    pub held_attempts: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
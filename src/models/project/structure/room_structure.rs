@@ -1,6 +1,7 @@
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "rooms")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -13,6 +14,11 @@ pub struct Model {
     pub bounding_box_area: f64,
     pub bounding_box_aspect: f64,
     pub rectangularity: f64,
+    pub polygon_area: Option<f64>,
+    pub perimeter: Option<f64>,
+    pub convexity: Option<f64>,
+    pub compactness: Option<f64>,
+    pub shape_descriptor: Option<Vec<f64>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
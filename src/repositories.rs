@@ -1,4 +1,15 @@
+pub mod change_stream_token_repository;
+pub mod dependency_probe;
+pub mod fixture_loader;
 pub mod floor_structure_repository;
+pub mod floorplan_cache;
+pub mod floorplan_fetcher;
 pub mod image_repository;
+pub mod index_watermark_repository;
+pub mod job_repository;
+pub mod object_store;
 pub mod project_repository;
 pub mod room_structure_repository;
+pub mod structure_index;
+pub mod structure_index_error_repository;
+pub mod structure_store;
@@ -0,0 +1,67 @@
+use anyhow::anyhow;
+use mongodb::{
+    bson::{self, Document, doc},
+    change_stream::event::ResumeToken,
+};
+use sea_orm::{ActiveValue::Set, DatabaseConnection, EntityTrait, sea_query::OnConflict};
+
+use crate::{error::ApiError, models::project::structure::change_stream_token};
+
+#[derive(Clone)]
+pub struct ChangeStreamTokenRepository {
+    db: DatabaseConnection,
+}
+
+impl ChangeStreamTokenRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn find(&self, name: &str) -> Result<Option<ResumeToken>, ApiError> {
+        let Some(model) = change_stream_token::Entity::find_by_id(name.to_string())
+            .one(&self.db)
+            .await
+            .map_err(ApiError::database)?
+        else {
+            return Ok(None);
+        };
+
+        let document: Document = bson::from_slice(&model.token).map_err(ApiError::internal)?;
+        let token = document
+            .get("token")
+            .cloned()
+            .ok_or_else(|| ApiError::internal(anyhow!("resume token {name} is empty")))?;
+
+        bson::from_bson(token).map(Some).map_err(ApiError::internal)
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<(), ApiError> {
+        change_stream_token::Entity::delete_by_id(name.to_string())
+            .exec(&self.db)
+            .await
+            .map_err(ApiError::database)?;
+        Ok(())
+    }
+
+    pub async fn save(&self, name: &str, token: &ResumeToken) -> Result<(), ApiError> {
+        let token = bson::to_bson(token).map_err(ApiError::internal)?;
+        let payload = bson::to_vec(&doc! { "token": token }).map_err(ApiError::internal)?;
+
+        let model = change_stream_token::ActiveModel {
+            name: Set(name.to_string()),
+            token: Set(payload),
+        };
+
+        change_stream_token::Entity::insert(model)
+            .on_conflict(
+                OnConflict::column(change_stream_token::Column::Name)
+                    .update_column(change_stream_token::Column::Token)
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .map_err(ApiError::database)?;
+
+        Ok(())
+    }
+}
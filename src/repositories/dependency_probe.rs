@@ -0,0 +1,141 @@
+use std::{fmt::Display, future::Future, sync::Arc, time::Duration};
+
+use mongodb::{Database as MongoDatabase, bson::doc};
+use reqwest::Client as HttpClient;
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::repositories::object_store::ObjectStore;
+
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyState {
+    Up,
+    Down,
+    /// Not part of this deployment; never makes the instance unready.
+    NotConfigured,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyStatus {
+    pub name: &'static str,
+    pub status: DependencyState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Pings the databases, the object store and the CDN for the readiness endpoint.
+#[derive(Clone)]
+pub struct DependencyProbe {
+    mongo: Option<MongoDatabase>,
+    postgres: Option<DatabaseConnection>,
+    object_store: Arc<dyn ObjectStore>,
+    cdn: Option<(HttpClient, String)>,
+    timeout: Duration,
+}
+
+impl DependencyProbe {
+    pub fn new(object_store: Arc<dyn ObjectStore>) -> Self {
+        Self {
+            mongo: None,
+            postgres: None,
+            object_store,
+            cdn: None,
+            timeout: DEFAULT_PROBE_TIMEOUT,
+        }
+    }
+
+    pub fn with_mongo(mut self, mongo: Option<MongoDatabase>) -> Self {
+        self.mongo = mongo;
+        self
+    }
+
+    pub fn with_postgres(mut self, postgres: Option<DatabaseConnection>) -> Self {
+        self.postgres = postgres;
+        self
+    }
+
+    pub fn with_cdn(mut self, http_client: HttpClient, base_url: String) -> Self {
+        self.cdn = Some((http_client, base_url));
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Check every dependency concurrently, each bounded by the probe timeout.
+    pub async fn check(&self) -> Vec<DependencyStatus> {
+        let (mongo, postgres, object_store, cdn) = tokio::join!(
+            self.probe(
+                "mongo",
+                self.mongo.as_ref().map(|db| async move {
+                    db.run_command(doc! { "ping": 1 }, None).await.map(drop)
+                }),
+            ),
+            self.probe(
+                "postgres",
+                self.postgres.as_ref().map(|db| async move {
+                    let statement = Statement::from_string(db.get_database_backend(), "SELECT 1");
+                    db.query_one(statement).await.map(drop)
+                }),
+            ),
+            self.probe("objectStore", Some(self.object_store.ping())),
+            self.probe(
+                "cdn",
+                self.cdn.as_ref().map(|(client, base_url)| async move {
+                    let response = client.head(base_url).send().await?;
+                    response
+                        .error_for_status()
+                        .map(drop)
+                        .or_else(|err| match err.status() {
+                            Some(status) if !status.is_server_error() => Ok(()),
+                            _ => Err(err),
+                        })
+                }),
+            ),
+        );
+
+        vec![mongo, postgres, object_store, cdn]
+    }
+
+    async fn probe<F, E>(&self, name: &'static str, check: Option<F>) -> DependencyStatus
+    where
+        F: Future<Output = Result<(), E>>,
+        E: Display,
+    {
+        let Some(check) = check else {
+            return DependencyStatus {
+                name,
+                status: DependencyState::NotConfigured,
+                latency_ms: None,
+                error: None,
+            };
+        };
+
+        let started = Instant::now();
+        let error = match tokio::time::timeout(self.timeout, check).await {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(err.to_string()),
+            Err(_) => Some(format!("timed out after {}ms", self.timeout.as_millis())),
+        };
+
+        DependencyStatus {
+            name,
+            status: if error.is_some() {
+                DependencyState::Down
+            } else {
+                DependencyState::Up
+            },
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            error,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    models::{image::Image, project::Project},
+    repositories::{
+        image_repository::InMemoryImageRepository, project_repository::InMemoryProjectRepository,
+    },
+};
+
+/// Projects and images read from a fixture directory laid out like the object store:
+///
+/// ```text
+/// projects/{id}/project.json     the project document
+/// projects/{id}/floorplans.json  served by the local object store
+/// images.json                    optional array of image documents
+/// ```
+pub struct Fixtures {
+    pub projects: InMemoryProjectRepository,
+    pub images: InMemoryImageRepository,
+}
+
+pub fn load_fixtures(root: &Path) -> Result<Fixtures> {
+    let projects = InMemoryProjectRepository::default();
+    let images = InMemoryImageRepository::default();
+
+    let projects_dir = root.join("projects");
+    let entries = std::fs::read_dir(&projects_dir)
+        .with_context(|| format!("failed to read {}", projects_dir.display()))?;
+    for entry in entries {
+        let directory = entry?.path();
+        let path = directory.join("project.json");
+        if !path.is_file() {
+            continue;
+        }
+
+        let mut project: Project = read_json(&path)?;
+        if project.id.is_none() {
+            project.id = directory
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(str::to_string);
+        }
+        projects.insert(project)?;
+    }
+
+    let images_path = root.join("images.json");
+    if images_path.is_file() {
+        let documents: Vec<Image> = read_json(&images_path)?;
+        for image in documents {
+            images.insert(image)?;
+        }
+    }
+
+    Ok(Fixtures { projects, images })
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T> {
+    let payload =
+        std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_slice(&payload).with_context(|| format!("failed to parse {}", path.display()))
+}
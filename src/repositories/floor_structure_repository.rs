@@ -1,20 +1,34 @@
-use std::cmp::Ordering;
-
+use anyhow::anyhow;
 use sea_orm::{
+    ActiveModelTrait,
     ActiveValue::Set,
-    ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, FromQueryResult, Order,
-    Statement,
-    sea_query::{Alias, Expr, ExprTrait, Func, OnConflict, Query},
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    FromQueryResult, Iterable, Order, QueryFilter, Statement, TransactionTrait,
+    sea_query::{
+        Alias, Asterisk, Expr, ExprTrait, Func, IntoIden, OnConflict, Query, SelectStatement,
+        SimpleExpr,
+    },
 };
 
 use crate::{
     error::ApiError,
-    models::project::structure::{FloorStructureColumn, FloorStructureEntity, floor_structure},
+    models::project::{
+        child::structure::GraphSignature,
+        structure::{FloorStructureColumn, FloorStructureEntity, floor_structure},
+    },
+    repositories::structure_store::{DEFAULT_UPSERT_CHUNK_SIZE, WriteSummary, clamp_chunk_size},
+    scoring::{
+        FloorScoring,
+        embedding::{floor_embedding, to_vector_literal},
+    },
+    utils::cursor::{SimilarityCursor, remaining_candidates},
 };
 
 #[derive(Clone)]
 pub struct FloorStructureRepository {
     db: DatabaseConnection,
+    ann_candidates: Option<u64>,
+    chunk_size: usize,
 }
 
 #[allow(dead_code)]
@@ -24,7 +38,15 @@ pub struct SimilarFloor {
     pub title: String,
     pub project_id: String,
     pub area: f64,
+    pub room_count: i32,
+    pub bounding_box_aspect: f64,
+    pub rectangularity: f64,
     pub score: f64,
+    pub area_distance: f64,
+    pub aspect_distance: f64,
+    pub rectangularity_distance: f64,
+    pub room_count_distance: f64,
+    pub topology_distance: f64,
 }
 
 #[derive(Clone, Debug)]
@@ -39,6 +61,7 @@ pub struct FloorStructureRecord {
     pub bounding_box_area: f64,
     pub bounding_box_aspect: f64,
     pub rectangularity: f64,
+    pub graph_signature: Option<GraphSignature>,
 }
 
 impl From<FloorStructureRecord> for floor_structure::ActiveModel {
@@ -54,20 +77,67 @@ impl From<FloorStructureRecord> for floor_structure::ActiveModel {
             bounding_box_area: Set(record.bounding_box_area),
             bounding_box_aspect: Set(record.bounding_box_aspect),
             rectangularity: Set(record.rectangularity),
+            graph_signature: Set(record
+                .graph_signature
+                .and_then(|signature| serde_json::to_value(signature).ok())),
+        }
+    }
+}
+
+impl From<FloorStructureRecord> for floor_structure::Model {
+    fn from(record: FloorStructureRecord) -> Self {
+        floor_structure::Model {
+            id: record.id,
+            title: record.title,
+            project_id: record.project_id,
+            area: record.area,
+            room_count: record.room_count,
+            bounding_box_width: record.bounding_box_width,
+            bounding_box_depth: record.bounding_box_depth,
+            bounding_box_area: record.bounding_box_area,
+            bounding_box_aspect: record.bounding_box_aspect,
+            rectangularity: record.rectangularity,
+            graph_signature: record
+                .graph_signature
+                .and_then(|signature| serde_json::to_value(signature).ok()),
         }
     }
 }
 
 impl FloorStructureRepository {
     pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+        Self {
+            db,
+            ann_candidates: None,
+            chunk_size: DEFAULT_UPSERT_CHUNK_SIZE,
+        }
+    }
+
+    /// Rows written per multi-row upsert statement, at most what fits the bind parameter limit.
+    pub fn with_chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        if let Some(chunk_size) = chunk_size.filter(|chunk_size| *chunk_size > 0) {
+            // Every column is bound, plus the embedding.
+            self.chunk_size =
+                clamp_chunk_size(chunk_size, FloorStructureColumn::iter().count() + 1);
+        }
+        self
+    }
+
+    pub fn connection(&self) -> &DatabaseConnection {
+        &self.db
+    }
+
+    /// Rank only the `candidates` nearest floors by embedding instead of scanning the table.
+    pub fn with_ann_candidates(mut self, candidates: Option<u64>) -> Self {
+        self.ann_candidates = candidates.filter(|candidates| *candidates > 0);
+        self
     }
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<floor_structure::Model>, ApiError> {
         FloorStructureEntity::find_by_id(id.to_string())
             .one(&self.db)
             .await
-            .map_err(ApiError::internal)
+            .map_err(ApiError::database)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -80,8 +150,12 @@ impl FloorStructureRepository {
         area_to: f64,
         aspect: f64,
         rectangularity: f64,
+        graph_signature: Option<&GraphSignature>,
+        scoring: &FloorScoring,
         k: u64,
+        after: Option<&SimilarityCursor>,
     ) -> Result<Vec<SimilarFloor>, ApiError> {
+        let k = remaining_candidates(self.ann_candidates, k, after);
         if k == 0 {
             return Ok(Vec::new());
         }
@@ -89,7 +163,7 @@ impl FloorStructureRepository {
         let area_dist = Func::abs(
             Expr::col((floor_structure::Entity, FloorStructureColumn::Area)).sub(Expr::value(area)),
         )
-        .div(Expr::value(area.max(30.0_f64)));
+        .div(Expr::value(area.max(scoring.min_area_normalizer)));
         let aspect_dist = Func::abs(
             Expr::col((
                 floor_structure::Entity,
@@ -111,13 +185,30 @@ impl FloorStructureRepository {
         let room_count_dist = room_count_diff
             .clone()
             .div(room_count_diff.add(Expr::value(room_count.max(1) as f64)));
+        let topology_dist = match graph_signature {
+            Some(signature) => topology_distance(signature),
+            None => Expr::value(0.0_f64),
+        };
 
         let score_expr = area_dist
             .clone()
-            .mul(Expr::value(0.3_f64))
-            .add(aspect_dist.clone().mul(Expr::value(0.3_f64)))
-            .add(rectangularity_dist.clone().mul(Expr::value(0.2_f64)))
-            .add(room_count_dist.clone().mul(Expr::value(0.2_f64)));
+            .mul(Expr::value(scoring.area_weight))
+            .add(aspect_dist.clone().mul(Expr::value(scoring.aspect_weight)))
+            .add(
+                rectangularity_dist
+                    .clone()
+                    .mul(Expr::value(scoring.rectangularity_weight)),
+            )
+            .add(
+                room_count_dist
+                    .clone()
+                    .mul(Expr::value(scoring.room_count_weight)),
+            )
+            .add(
+                topology_dist
+                    .clone()
+                    .mul(Expr::value(scoring.topology_weight)),
+            );
 
         let score_alias = Alias::new("score");
         let subquery_alias = Alias::new("distinct_floors");
@@ -132,8 +223,21 @@ impl FloorStructureRepository {
                 Expr::col((floor_structure::Entity, FloorStructureColumn::Area)),
                 Alias::new("area"),
             )
+            .column((floor_structure::Entity, FloorStructureColumn::RoomCount))
+            .column((
+                floor_structure::Entity,
+                FloorStructureColumn::BoundingBoxAspect,
+            ))
+            .column((
+                floor_structure::Entity,
+                FloorStructureColumn::Rectangularity,
+            ))
             .expr_as(score_expr.clone(), score_alias.clone())
-            .from(floor_structure::Entity)
+            .expr_as(area_dist, Alias::new("area_distance"))
+            .expr_as(aspect_dist, Alias::new("aspect_distance"))
+            .expr_as(rectangularity_dist, Alias::new("rectangularity_distance"))
+            .expr_as(room_count_dist, Alias::new("room_count_distance"))
+            .expr_as(topology_dist, Alias::new("topology_distance"))
             .and_where(
                 Expr::col((floor_structure::Entity, FloorStructureColumn::ProjectId))
                     .ne(exclude_project_id),
@@ -148,8 +252,8 @@ impl FloorStructureRepository {
                     FloorStructureColumn::BoundingBoxAspect,
                 ))
                 .between(
-                    Expr::value(aspect * 0.85_f64),
-                    Expr::value(aspect * 1.15_f64),
+                    Expr::value(aspect * (1.0 - scoring.aspect_tolerance)),
+                    Expr::value(aspect * (1.0 + scoring.aspect_tolerance)),
                 ),
             )
             .and_where(
@@ -158,13 +262,15 @@ impl FloorStructureRepository {
                     FloorStructureColumn::Rectangularity,
                 ))
                 .between(
-                    Expr::value(rectangularity - 0.1_f64),
-                    Expr::value(rectangularity + 0.1_f64),
+                    Expr::value(rectangularity - scoring.rectangularity_tolerance),
+                    Expr::value(rectangularity + scoring.rectangularity_tolerance),
                 ),
             )
             .and_where(
-                Expr::col((floor_structure::Entity, FloorStructureColumn::RoomCount))
-                    .between(Expr::value(room_count - 3), Expr::value(room_count + 3)),
+                Expr::col((floor_structure::Entity, FloorStructureColumn::RoomCount)).between(
+                    Expr::value(room_count - scoring.room_count_tolerance),
+                    Expr::value(room_count + scoring.room_count_tolerance),
+                ),
             )
             .order_by(
                 (floor_structure::Entity, FloorStructureColumn::ProjectId),
@@ -172,63 +278,226 @@ impl FloorStructureRepository {
             )
             .order_by_expr(score_expr.clone(), Order::Asc);
 
+        // The band filters above act as post-filters on the approximate neighbours.
+        match self.ann_candidates {
+            Some(candidates) => distinct_per_project.from_subquery(
+                nearest_floors(
+                    exclude_project_id,
+                    &floor_embedding(area, aspect, rectangularity, room_count),
+                    candidates,
+                ),
+                floor_structure::Entity,
+            ),
+            None => distinct_per_project.from(floor_structure::Entity),
+        };
+
         let mut ordered_select = Query::select();
         ordered_select
             .column((subquery_alias.clone(), Alias::new("id")))
             .column((subquery_alias.clone(), Alias::new("title")))
             .column((subquery_alias.clone(), Alias::new("project_id")))
             .column((subquery_alias.clone(), Alias::new("area")))
+            .column((subquery_alias.clone(), Alias::new("room_count")))
+            .column((subquery_alias.clone(), Alias::new("bounding_box_aspect")))
+            .column((subquery_alias.clone(), Alias::new("rectangularity")))
             .column((subquery_alias.clone(), score_alias.clone()))
+            .column((subquery_alias.clone(), Alias::new("area_distance")))
+            .column((subquery_alias.clone(), Alias::new("aspect_distance")))
+            .column((
+                subquery_alias.clone(),
+                Alias::new("rectangularity_distance"),
+            ))
+            .column((subquery_alias.clone(), Alias::new("room_count_distance")))
+            .column((subquery_alias.clone(), Alias::new("topology_distance")))
             .from_subquery(distinct_per_project, subquery_alias.clone())
             .order_by((subquery_alias.clone(), score_alias.clone()), Order::Asc)
             .order_by((subquery_alias.clone(), Alias::new("id")), Order::Asc)
-            // .limit(k)
-            ;
+            .limit(k);
+
+        if let Some(cursor) = after {
+            let score_col = Expr::col((subquery_alias.clone(), score_alias.clone()));
+            let id_col = Expr::col((subquery_alias.clone(), Alias::new("id")));
+            ordered_select.and_where(
+                score_col.clone().gt(cursor.score).or(score_col
+                    .eq(cursor.score)
+                    .and(id_col.gt(cursor.id.as_str()))),
+            );
+        }
 
         let backend: DatabaseBackend = self.db.get_database_backend();
         let stmt: Statement = backend.build(&ordered_select);
 
-        let mut results = SimilarFloor::find_by_statement(stmt)
+        // Already in `(score, id)` order, which the cursor relies on.
+        SimilarFloor::find_by_statement(stmt)
             .all(&self.db)
             .await
-            .map_err(ApiError::internal)?;
+            .map_err(ApiError::database)
+    }
 
-        results.sort_by(|a, b| match a.score.partial_cmp(&b.score) {
-            Some(ordering) if ordering != Ordering::Equal => ordering,
-            _ => a.id.cmp(&b.id),
-        });
+    /// Replace every floor of `project_id` with `records`, dropping floors that no longer exist.
+    pub async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<FloorStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        let txn = self.db.begin().await.map_err(ApiError::database)?;
+        let summary = self.replace_in(&txn, project_id, records).await?;
+        txn.commit().await.map_err(ApiError::database)?;
 
-        Ok(results)
+        Ok(summary)
     }
 
-    pub async fn save_all(&self, records: Vec<FloorStructureRecord>) -> Result<(), ApiError> {
-        if records.is_empty() {
-            return Ok(());
-        }
+    /// [`Self::replace_by_project_id`] on a caller-owned connection or transaction.
+    pub async fn replace_in<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        project_id: &str,
+        records: Vec<FloorStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        let ids: Vec<String> = records.iter().map(|record| record.id.clone()).collect();
 
-        for record in records {
-            let model: floor_structure::ActiveModel = record.into();
-            FloorStructureEntity::insert(model)
-                .on_conflict(
-                    OnConflict::column(FloorStructureColumn::Id)
-                        .update_columns([
-                            FloorStructureColumn::Title,
-                            FloorStructureColumn::ProjectId,
-                            FloorStructureColumn::Area,
-                            FloorStructureColumn::RoomCount,
-                            FloorStructureColumn::BoundingBoxWidth,
-                            FloorStructureColumn::BoundingBoxDepth,
-                            FloorStructureColumn::BoundingBoxArea,
-                            FloorStructureColumn::BoundingBoxAspect,
-                            FloorStructureColumn::Rectangularity,
-                        ])
-                        .to_owned(),
-                )
-                .exec(&self.db)
-                .await
-                .map_err(ApiError::internal)?;
+        let deleted = FloorStructureEntity::delete_many()
+            .filter(FloorStructureColumn::ProjectId.eq(project_id))
+            .filter(FloorStructureColumn::Id.is_not_in(ids))
+            .exec(db)
+            .await
+            .map_err(ApiError::database)?;
+
+        let mut summary = upsert_floors(db, &records, self.chunk_size).await?;
+        summary.removed = deleted.rows_affected;
+
+        Ok(summary)
+    }
+}
+
+/// Upsert `records` with one multi-row statement per chunk, embeddings included.
+///
+/// `xmax = 0` only holds for freshly inserted tuples, which tells inserts and updates apart.
+async fn upsert_floors<C: ConnectionTrait>(
+    db: &C,
+    records: &[FloorStructureRecord],
+    chunk_size: usize,
+) -> Result<WriteSummary, ApiError> {
+    let mut summary = WriteSummary::default();
+
+    for chunk in records.chunks(chunk_size.max(1)) {
+        let mut insert = Query::insert();
+        insert
+            .into_table(floor_structure::Entity)
+            .columns(
+                FloorStructureColumn::iter()
+                    .map(IntoIden::into_iden)
+                    .chain([Alias::new("embedding").into_iden()]),
+            )
+            .on_conflict(
+                OnConflict::column(FloorStructureColumn::Id)
+                    .update_columns(
+                        FloorStructureColumn::iter()
+                            .filter(|column| !matches!(column, FloorStructureColumn::Id)),
+                    )
+                    .update_column(Alias::new("embedding"))
+                    .to_owned(),
+            )
+            .returning(Query::returning().expr(Expr::cust("xmax = 0")));
+
+        for record in chunk {
+            let embedding = floor_embedding(
+                record.area,
+                record.bounding_box_aspect,
+                record.rectangularity,
+                record.room_count,
+            );
+            let model: floor_structure::ActiveModel = record.clone().into();
+            let mut values = FloorStructureColumn::iter()
+                .map(|column| model.get(column).into_value().map(Expr::value))
+                .collect::<Option<Vec<SimpleExpr>>>()
+                .ok_or_else(|| {
+                    ApiError::internal(anyhow!("floor {} has unset columns", record.id))
+                })?;
+            values.push(Expr::cust_with_exprs(
+                "$1::vector",
+                [Expr::value(to_vector_literal(&embedding))],
+            ));
+            insert.values(values).map_err(ApiError::internal)?;
         }
 
-        Ok(())
+        let rows = db
+            .query_all(DatabaseBackend::Postgres.build(&insert))
+            .await
+            .map_err(ApiError::database)?;
+        for row in rows {
+            if row
+                .try_get_by_index::<bool>(0)
+                .map_err(ApiError::database)?
+            {
+                summary.inserted += 1;
+            } else {
+                summary.updated += 1;
+            }
+        }
     }
+
+    Ok(summary)
+}
+
+/// Approximate nearest neighbours of `embedding`, served by the HNSW index on `floors.embedding`.
+fn nearest_floors(exclude_project_id: &str, embedding: &[f64], candidates: u64) -> SelectStatement {
+    Query::select()
+        .column(Asterisk)
+        .from(floor_structure::Entity)
+        .and_where(
+            Expr::col((floor_structure::Entity, FloorStructureColumn::ProjectId))
+                .ne(exclude_project_id),
+        )
+        .and_where(Expr::col((floor_structure::Entity, Alias::new("embedding"))).is_not_null())
+        .order_by_expr(
+            Expr::cust_with_exprs(
+                "$1 <-> $2::vector",
+                [
+                    Expr::col((floor_structure::Entity, Alias::new("embedding"))).into(),
+                    Expr::value(to_vector_literal(embedding)),
+                ],
+            ),
+            Order::Asc,
+        )
+        .limit(candidates)
+        .to_owned()
+}
+
+/// Mean weighted Jaccard distance between the stored and the query graph signature histograms.
+///
+/// Floors without a stored signature get the maximum distance.
+fn topology_distance(signature: &GraphSignature) -> SimpleExpr {
+    let column = Expr::col((
+        floor_structure::Entity,
+        FloorStructureColumn::GraphSignature,
+    ));
+    let histograms = [
+        ("roomTypes", &signature.room_types),
+        ("adjacencies", &signature.adjacencies),
+        ("wlLabels", &signature.wl_labels),
+    ];
+
+    let count = histograms.len() as f64;
+    histograms
+        .into_iter()
+        .map(|(key, histogram)| {
+            let query = serde_json::to_string(histogram).unwrap_or_else(|_| "{}".to_string());
+            Expr::cust_with_exprs(
+                format!(
+                    "(SELECT CASE \
+                        WHEN COALESCE(SUM(GREATEST(COALESCE(c.v, 0), COALESCE(q.v, 0))), 0) = 0 THEN 0.0 \
+                        ELSE 1.0 - SUM(LEAST(COALESCE(c.v, 0), COALESCE(q.v, 0))) \
+                            / SUM(GREATEST(COALESCE(c.v, 0), COALESCE(q.v, 0))) \
+                    END \
+                    FROM (SELECT key, value::float8 AS v FROM jsonb_each_text($1 -> '{key}')) c \
+                    FULL OUTER JOIN (SELECT key, value::float8 AS v FROM jsonb_each_text($2::jsonb)) q \
+                        ON c.key = q.key)"
+                ),
+                [column.clone().into(), Expr::value(query)],
+            )
+        })
+        .reduce(|acc, distance| acc.add(distance))
+        .map(|sum| sum.div(Expr::value(count)))
+        .unwrap_or_else(|| Expr::value(0.0_f64))
 }
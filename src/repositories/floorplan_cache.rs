@@ -0,0 +1,126 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{models::project::child::floorplan::Floorplan, repositories::object_store::Validators};
+
+#[derive(Clone)]
+pub struct CachedFloorplans {
+    pub floorplans: Arc<Vec<Floorplan>>,
+    pub validators: Validators,
+}
+
+struct Entry {
+    cached: CachedFloorplans,
+    size: usize,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    /// `last_used` tick -> project id, oldest first.
+    recency: BTreeMap<u64, String>,
+    bytes: usize,
+    tick: u64,
+}
+
+/// Parsed floorplan documents keyed by project id, evicted least recently used first
+/// once the raw documents they came from exceed `capacity_bytes`.
+pub struct FloorplanCache {
+    capacity_bytes: usize,
+    inner: Mutex<Inner>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheUsage {
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+impl FloorplanCache {
+    pub fn new(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes,
+            inner: Mutex::default(),
+        }
+    }
+
+    pub fn get(&self, project_id: &str) -> Option<CachedFloorplans> {
+        let mut inner = self.lock();
+        let tick = inner.next_tick();
+        let entry = inner.entries.get_mut(project_id)?;
+        let previous = std::mem::replace(&mut entry.last_used, tick);
+        let cached = entry.cached.clone();
+        inner.recency.remove(&previous);
+        inner.recency.insert(tick, project_id.to_string());
+        Some(cached)
+    }
+
+    /// Store a freshly downloaded document. `size` is the length of the raw payload.
+    pub fn insert(&self, project_id: &str, cached: CachedFloorplans, size: usize) {
+        if size > self.capacity_bytes {
+            self.remove(project_id);
+            return;
+        }
+
+        let mut inner = self.lock();
+        inner.remove(project_id);
+        while inner.bytes + size > self.capacity_bytes {
+            let Some((_, oldest)) = inner.recency.pop_first() else {
+                break;
+            };
+            if let Some(entry) = inner.entries.remove(&oldest) {
+                inner.bytes -= entry.size;
+            }
+        }
+
+        let tick = inner.next_tick();
+        inner.bytes += size;
+        inner.recency.insert(tick, project_id.to_string());
+        inner.entries.insert(
+            project_id.to_string(),
+            Entry {
+                cached,
+                size,
+                last_used: tick,
+            },
+        );
+    }
+
+    pub fn remove(&self, project_id: &str) {
+        self.lock().remove(project_id);
+    }
+
+    pub fn usage(&self) -> CacheUsage {
+        let inner = self.lock();
+        CacheUsage {
+            entries: inner.entries.len(),
+            bytes: inner.bytes,
+        }
+    }
+
+    /// Nothing panics while the lock is held, so a poisoned cache is recovered instead of
+    /// failing every later request.
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Inner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, project_id: &str) {
+        if let Some(entry) = self.entries.remove(project_id) {
+            self.recency.remove(&entry.last_used);
+            self.bytes -= entry.size;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
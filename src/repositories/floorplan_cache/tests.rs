@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use super::{CachedFloorplans, FloorplanCache};
+use crate::repositories::object_store::Validators;
+
+fn cached(etag: &str) -> CachedFloorplans {
+    CachedFloorplans {
+        floorplans: Arc::default(),
+        validators: Validators {
+            etag: Some(etag.to_string()),
+            last_modified: None,
+        },
+    }
+}
+
+fn etag(cache: &FloorplanCache, project_id: &str) -> Option<String> {
+    cache
+        .get(project_id)
+        .and_then(|cached| cached.validators.etag)
+}
+
+#[test]
+fn evicts_the_least_recently_used_project() {
+    let cache = FloorplanCache::new(100);
+    cache.insert("a", cached("a1"), 40);
+    cache.insert("b", cached("b1"), 40);
+    // Reading `a` makes `b` the least recently used entry.
+    assert_eq!(etag(&cache, "a").as_deref(), Some("a1"));
+
+    cache.insert("c", cached("c1"), 40);
+
+    assert_eq!(etag(&cache, "b"), None);
+    assert_eq!(etag(&cache, "a").as_deref(), Some("a1"));
+    assert_eq!(etag(&cache, "c").as_deref(), Some("c1"));
+    let usage = cache.usage();
+    assert_eq!((usage.entries, usage.bytes), (2, 80));
+}
+
+#[test]
+fn accounts_for_replaced_and_removed_documents() {
+    let cache = FloorplanCache::new(100);
+    cache.insert("a", cached("a1"), 30);
+    cache.insert("b", cached("b1"), 20);
+
+    cache.insert("a", cached("a2"), 50);
+    assert_eq!(etag(&cache, "a").as_deref(), Some("a2"));
+    assert_eq!(cache.usage().bytes, 70);
+
+    cache.remove("b");
+    cache.remove("missing");
+    let usage = cache.usage();
+    assert_eq!((usage.entries, usage.bytes), (1, 50));
+}
+
+#[test]
+fn does_not_keep_documents_larger_than_the_capacity() {
+    let cache = FloorplanCache::new(100);
+    cache.insert("a", cached("a1"), 60);
+
+    // A stale copy of a document that outgrew the cache must not be served either.
+    cache.insert("a", cached("a2"), 101);
+
+    assert_eq!(etag(&cache, "a"), None);
+    let usage = cache.usage();
+    assert_eq!((usage.entries, usage.bytes), (0, 0));
+}
+
+#[test]
+fn keeps_working_after_a_panic_while_locked() {
+    let cache = Arc::new(FloorplanCache::new(100));
+    cache.insert("a", cached("a1"), 10);
+
+    let poisoner = cache.clone();
+    let result = std::thread::spawn(move || {
+        let _guard = poisoner.inner.lock().expect("unpoisoned");
+        panic!("poison the cache");
+    })
+    .join();
+    assert!(result.is_err());
+    assert!(cache.inner.is_poisoned());
+
+    assert_eq!(etag(&cache, "a").as_deref(), Some("a1"));
+    cache.insert("b", cached("b1"), 10);
+    assert_eq!(cache.usage().entries, 2);
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+use crate::{
+    error::IndexFailure,
+    models::project::{
+        Project, child::floorplan::Floorplan, structure::index_error::IndexFailureReason,
+    },
+    repositories::{
+        floorplan_cache::{CachedFloorplans, FloorplanCache},
+        object_store::{GetOutcome, ObjectStore, ObjectStoreError},
+    },
+};
+
+#[derive(Clone, Debug)]
+pub struct FetchOptions {
+    /// Deadline for a single attempt, including reading the body.
+    pub timeout: Duration,
+    /// Attempts after the first one for connection errors, timeouts and 5xx responses.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on each further attempt up to `max_backoff`,
+    /// then jittered so callers that failed together do not retry together.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Requests in flight at once across every caller.
+    pub concurrency: usize,
+    /// Upper bound on the raw size of documents kept in the parsed-floorplan cache.
+    pub cache_bytes: usize,
+}
+
+impl FetchOptions {
+    /// Backoff before retry number `attempt + 1`, before jitter.
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(10),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            concurrency: 8,
+            cache_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Loads `projects/{id}/floorplans.json` from the object store.
+#[derive(Clone)]
+pub struct FloorplanFetcher {
+    object_store: Arc<dyn ObjectStore>,
+    options: FetchOptions,
+    permits: Arc<Semaphore>,
+    cache: Arc<FloorplanCache>,
+    metrics: Arc<FetchMetrics>,
+}
+
+#[derive(Default)]
+struct FetchMetrics {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    successes: AtomicU64,
+    not_modified: AtomicU64,
+    not_found: AtomicU64,
+    failures: AtomicU64,
+    latency_ms_total: AtomicU64,
+    latency_ms_max: AtomicU64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchMetricsSnapshot {
+    pub requests: u64,
+    pub retries: u64,
+    pub successes: u64,
+    pub not_modified: u64,
+    pub not_found: u64,
+    pub failures: u64,
+    pub latency_ms_avg: f64,
+    pub latency_ms_max: u64,
+    pub cache_entries: usize,
+    pub cache_bytes: usize,
+}
+
+enum Attempt {
+    Done(Arc<Vec<Floorplan>>),
+    Retry(IndexFailure),
+    Fail(IndexFailure),
+}
+
+impl FloorplanFetcher {
+    pub fn new(object_store: Arc<dyn ObjectStore>, options: FetchOptions) -> Self {
+        let permits = Arc::new(Semaphore::new(options.concurrency.max(1)));
+        let cache = Arc::new(FloorplanCache::new(options.cache_bytes));
+        Self {
+            object_store,
+            options,
+            permits,
+            cache,
+            metrics: Arc::default(),
+        }
+    }
+
+    /// Fill `project.floorplans`; a project without a floorplan file keeps an empty list.
+    pub async fn populate(&self, project: &mut Project) -> Result<(), IndexFailure> {
+        if project
+            .floorplan_path
+            .as_deref()
+            .is_none_or(|path| path.is_empty())
+        {
+            return Ok(());
+        }
+        let Some(project_id) = project.id.as_deref() else {
+            return Ok(());
+        };
+
+        project.floorplans = self.fetch(project_id).await?;
+        Ok(())
+    }
+
+    /// Read `projects/{project_id}/floorplans.json`, revalidating a cached copy
+    /// instead of parsing it again.
+    pub async fn fetch(&self, project_id: &str) -> Result<Arc<Vec<Floorplan>>, IndexFailure> {
+        let key = Project::floorplan_key(project_id);
+
+        let mut attempt = 0;
+        loop {
+            // The permit only covers the request, so callers waiting out a backoff do not
+            // hold back everyone else.
+            let permit = self.permits.acquire().await.map_err(|err| {
+                IndexFailure::new(
+                    IndexFailureReason::Internal,
+                    format!("fetcher closed: {err}"),
+                )
+            })?;
+            let started = Instant::now();
+            let cached = self.cache.get(project_id);
+            let outcome = self.attempt(project_id, &key, cached).await;
+            self.record_latency(started.elapsed());
+            drop(permit);
+
+            match outcome {
+                Attempt::Done(floorplans) => return Ok(floorplans),
+                Attempt::Retry(failure) if attempt < self.options.max_retries => {
+                    let delay = jitter(self.options.backoff(attempt), random_u64());
+                    attempt += 1;
+                    self.metrics.retries.fetch_add(1, Ordering::Relaxed);
+                    tracing::debug!(key, attempt, error = %failure, "retrying floorplan fetch");
+                    tokio::time::sleep(delay).await;
+                }
+                Attempt::Retry(failure) | Attempt::Fail(failure) => {
+                    self.metrics.failures.fetch_add(1, Ordering::Relaxed);
+                    return Err(failure);
+                }
+            }
+        }
+    }
+
+    pub fn metrics(&self) -> FetchMetricsSnapshot {
+        let requests = self.metrics.requests.load(Ordering::Relaxed);
+        let latency_ms_total = self.metrics.latency_ms_total.load(Ordering::Relaxed);
+        let cache = self.cache.usage();
+
+        FetchMetricsSnapshot {
+            requests,
+            retries: self.metrics.retries.load(Ordering::Relaxed),
+            successes: self.metrics.successes.load(Ordering::Relaxed),
+            not_modified: self.metrics.not_modified.load(Ordering::Relaxed),
+            not_found: self.metrics.not_found.load(Ordering::Relaxed),
+            failures: self.metrics.failures.load(Ordering::Relaxed),
+            latency_ms_avg: if requests > 0 {
+                latency_ms_total as f64 / requests as f64
+            } else {
+                0.0
+            },
+            latency_ms_max: self.metrics.latency_ms_max.load(Ordering::Relaxed),
+            cache_entries: cache.entries,
+            cache_bytes: cache.bytes,
+        }
+    }
+
+    async fn attempt(
+        &self,
+        project_id: &str,
+        key: &str,
+        cached: Option<CachedFloorplans>,
+    ) -> Attempt {
+        let validators = cached.as_ref().map(|cached| &cached.validators);
+        let outcome = match tokio::time::timeout(
+            self.options.timeout,
+            self.object_store.get(key, validators),
+        )
+        .await
+        {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(err)) => {
+                let retryable = err.is_retryable();
+                let failure = upstream(err);
+                return if retryable {
+                    Attempt::Retry(failure)
+                } else {
+                    Attempt::Fail(failure)
+                };
+            }
+            Err(_) => {
+                return Attempt::Retry(upstream(ObjectStoreError::Unavailable(format!(
+                    "timed out fetching {key}"
+                ))));
+            }
+        };
+
+        let (payload, validators) = match (outcome, cached) {
+            (GetOutcome::NotModified, Some(cached)) => {
+                self.metrics.not_modified.fetch_add(1, Ordering::Relaxed);
+                return Attempt::Done(cached.floorplans);
+            }
+            (GetOutcome::NotModified, None) => {
+                return Attempt::Fail(upstream(ObjectStoreError::Failed(format!(
+                    "unexpected not-modified response for {key}"
+                ))));
+            }
+            (GetOutcome::NotFound, _) => {
+                self.metrics.not_found.fetch_add(1, Ordering::Relaxed);
+                self.cache.remove(project_id);
+                return Attempt::Done(Arc::default());
+            }
+            (GetOutcome::Found { body, validators }, _) => (body, validators),
+        };
+
+        match serde_json::from_slice::<Vec<Floorplan>>(&payload) {
+            Ok(floorplans) => {
+                self.metrics.successes.fetch_add(1, Ordering::Relaxed);
+                let floorplans = Arc::new(floorplans);
+                if validators.is_empty() {
+                    self.cache.remove(project_id);
+                } else {
+                    let cached = CachedFloorplans {
+                        floorplans: floorplans.clone(),
+                        validators,
+                    };
+                    self.cache.insert(project_id, cached, payload.len());
+                }
+                Attempt::Done(floorplans)
+            }
+            Err(err) => Attempt::Fail(IndexFailure::new(
+                IndexFailureReason::ParseError,
+                format!("invalid floorplans at {key}: {err}"),
+            )),
+        }
+    }
+
+    fn record_latency(&self, elapsed: Duration) {
+        let elapsed_ms = elapsed.as_millis() as u64;
+        self.metrics.requests.fetch_add(1, Ordering::Relaxed);
+        self.metrics
+            .latency_ms_total
+            .fetch_add(elapsed_ms, Ordering::Relaxed);
+        self.metrics
+            .latency_ms_max
+            .fetch_max(elapsed_ms, Ordering::Relaxed);
+    }
+}
+
+fn upstream(err: ObjectStoreError) -> IndexFailure {
+    IndexFailure::new(IndexFailureReason::UpstreamError, err.to_string())
+}
+
+/// A delay between half of `delay` and all of it, picked by `sample`.
+fn jitter(delay: Duration, sample: u64) -> Duration {
+    let half = delay / 2;
+    half + half.mul_f64(sample as f64 / u64::MAX as f64)
+}
+
+/// Randomly keyed hash of nothing; enough entropy to spread retries without a RNG dependency.
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,204 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+
+use super::{FetchOptions, FloorplanFetcher, jitter};
+use crate::{
+    models::project::{Project, structure::index_error::IndexFailureReason},
+    repositories::object_store::{
+        GetOutcome, ObjectMeta, ObjectStore, ObjectStoreError, Validators,
+    },
+};
+
+/// Canned `get` outcomes per key; a key with nothing left is unavailable.
+#[derive(Default)]
+struct ScriptedStore {
+    outcomes: Mutex<HashMap<String, VecDeque<Result<GetOutcome, ObjectStoreError>>>>,
+    gets: AtomicUsize,
+}
+
+impl ScriptedStore {
+    fn script(
+        self,
+        project_id: &str,
+        outcomes: impl IntoIterator<Item = Result<GetOutcome, ObjectStoreError>>,
+    ) -> Self {
+        self.outcomes.lock().expect("unpoisoned").insert(
+            Project::floorplan_key(project_id),
+            outcomes.into_iter().collect(),
+        );
+        self
+    }
+
+    fn gets(&self) -> usize {
+        self.gets.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for ScriptedStore {
+    async fn list(&self, _prefix: &str) -> Result<Vec<ObjectMeta>, ObjectStoreError> {
+        Ok(Vec::new())
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+        _validators: Option<&Validators>,
+    ) -> Result<GetOutcome, ObjectStoreError> {
+        self.gets.fetch_add(1, Ordering::SeqCst);
+        self.outcomes
+            .lock()
+            .expect("unpoisoned")
+            .get_mut(key)
+            .and_then(VecDeque::pop_front)
+            .unwrap_or_else(|| Err(unavailable()))
+    }
+
+    async fn ping(&self) -> Result<(), ObjectStoreError> {
+        Ok(())
+    }
+}
+
+fn unavailable() -> ObjectStoreError {
+    ObjectStoreError::Unavailable("503 Service Unavailable".to_string())
+}
+
+fn found() -> Result<GetOutcome, ObjectStoreError> {
+    Ok(GetOutcome::Found {
+        body: b"[]".to_vec(),
+        validators: Validators::default(),
+    })
+}
+
+fn options(max_retries: u32, backoff: Duration) -> FetchOptions {
+    FetchOptions {
+        max_retries,
+        initial_backoff: backoff,
+        max_backoff: backoff,
+        ..FetchOptions::default()
+    }
+}
+
+fn fetcher(store: &Arc<ScriptedStore>, options: FetchOptions) -> FloorplanFetcher {
+    let object_store: Arc<dyn ObjectStore> = store.clone();
+    FloorplanFetcher::new(object_store, options)
+}
+
+#[test]
+fn backoff_doubles_up_to_the_maximum() {
+    let options = FetchOptions {
+        initial_backoff: Duration::from_millis(200),
+        max_backoff: Duration::from_secs(1),
+        ..FetchOptions::default()
+    };
+
+    let delays: Vec<_> = (0..5).map(|attempt| options.backoff(attempt)).collect();
+    assert_eq!(
+        delays,
+        [200, 400, 800, 1000, 1000].map(Duration::from_millis)
+    );
+    assert_eq!(options.backoff(u32::MAX), Duration::from_secs(1));
+}
+
+#[test]
+fn jitter_keeps_at_least_half_of_the_delay() {
+    let delay = Duration::from_millis(800);
+
+    assert_eq!(jitter(delay, 0), Duration::from_millis(400));
+    assert_eq!(jitter(delay, u64::MAX), delay);
+    let middle = jitter(delay, u64::MAX / 2);
+    assert!(middle > Duration::from_millis(590) && middle < Duration::from_millis(610));
+}
+
+#[tokio::test]
+async fn retries_unavailable_responses() {
+    let store = Arc::new(ScriptedStore::default().script(
+        "project-a",
+        [Err(unavailable()), Err(unavailable()), found()],
+    ));
+    let fetcher = fetcher(&store, options(3, Duration::ZERO));
+
+    let floorplans = fetcher.fetch("project-a").await.expect("fetched");
+    assert!(floorplans.is_empty());
+    assert_eq!(store.gets(), 3);
+    let metrics = fetcher.metrics();
+    assert_eq!(
+        (metrics.retries, metrics.successes, metrics.failures),
+        (2, 1, 0)
+    );
+}
+
+#[tokio::test]
+async fn gives_up_after_the_configured_retries() {
+    let store = Arc::new(ScriptedStore::default());
+    let fetcher = fetcher(&store, options(2, Duration::ZERO));
+
+    let failure = fetcher.fetch("project-a").await.expect_err("unavailable");
+    assert_eq!(failure.reason, IndexFailureReason::UpstreamError);
+    assert!(failure.is_transient());
+    assert_eq!(store.gets(), 3);
+    assert_eq!(fetcher.metrics().failures, 1);
+}
+
+#[tokio::test]
+async fn does_not_retry_permanent_failures() {
+    let store = Arc::new(ScriptedStore::default().script(
+        "project-a",
+        [Err(ObjectStoreError::Failed("403 Forbidden".to_string()))],
+    ));
+    let fetcher = fetcher(&store, options(3, Duration::ZERO));
+
+    let failure = fetcher.fetch("project-a").await.expect_err("forbidden");
+    assert_eq!(failure.reason, IndexFailureReason::UpstreamError);
+    assert_eq!(store.gets(), 1);
+    assert_eq!(fetcher.metrics().retries, 0);
+}
+
+#[tokio::test]
+async fn does_not_retry_unparseable_documents() {
+    let store = Arc::new(ScriptedStore::default().script(
+        "project-a",
+        [Ok(GetOutcome::Found {
+            body: b"{".to_vec(),
+            validators: Validators::default(),
+        })],
+    ));
+    let fetcher = fetcher(&store, options(3, Duration::ZERO));
+
+    let failure = fetcher.fetch("project-a").await.expect_err("unparseable");
+    assert_eq!(failure.reason, IndexFailureReason::ParseError);
+    assert!(!failure.is_transient());
+    assert_eq!(store.gets(), 1);
+}
+
+#[tokio::test]
+async fn backing_off_does_not_hold_a_permit() {
+    let store = Arc::new(ScriptedStore::default().script("project-b", [found()]));
+    let fetcher = fetcher(
+        &store,
+        FetchOptions {
+            concurrency: 1,
+            ..options(1, Duration::from_secs(30))
+        },
+    );
+
+    let backing_off = tokio::spawn({
+        let fetcher = fetcher.clone();
+        async move { fetcher.fetch("project-a").await }
+    });
+    while store.gets() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    let fetched = tokio::time::timeout(Duration::from_secs(5), fetcher.fetch("project-b")).await;
+    backing_off.abort();
+    assert!(fetched.expect("not blocked by the retrying fetch").is_ok());
+}
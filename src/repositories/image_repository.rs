@@ -1,3 +1,7 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
 use mongodb::{
     Collection, Database,
     bson::{Bson, doc},
@@ -6,6 +10,11 @@ use mongodb::{
 
 use crate::{error::ApiError, models::image::Image};
 
+#[async_trait]
+pub trait ImageStore: Send + Sync {
+    async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Image>, ApiError>;
+}
+
 #[derive(Clone)]
 pub struct ImageRepository {
     collection: Collection<Image>,
@@ -17,8 +26,11 @@ impl ImageRepository {
             collection: db.collection("images"),
         }
     }
+}
 
-    pub async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Image>, ApiError> {
+#[async_trait]
+impl ImageStore for ImageRepository {
+    async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Image>, ApiError> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
@@ -38,14 +50,44 @@ impl ImageRepository {
             .collection
             .find(filter, options)
             .await
-            .map_err(ApiError::internal)?;
+            .map_err(ApiError::mongo)?;
 
         let mut images = Vec::new();
-        while cursor.advance().await.map_err(ApiError::internal)? {
-            let image: Image = cursor.deserialize_current().map_err(ApiError::internal)?;
+        while cursor.advance().await.map_err(ApiError::mongo)? {
+            let image: Image = cursor.deserialize_current().map_err(ApiError::mongo)?;
             images.push(image);
         }
 
         Ok(images)
     }
 }
+
+/// Images held in process memory, e.g. loaded from a fixture directory.
+#[derive(Default)]
+pub struct InMemoryImageRepository {
+    images: RwLock<HashMap<String, Image>>,
+}
+
+impl InMemoryImageRepository {
+    pub fn insert(&self, image: Image) -> Result<(), ApiError> {
+        self.images
+            .write()
+            .map_err(|_| ApiError::internal(anyhow!("image repository lock poisoned")))?
+            .insert(image.id.clone(), image);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ImageStore for InMemoryImageRepository {
+    async fn find_by_ids(&self, ids: &[String]) -> Result<Vec<Image>, ApiError> {
+        let images = self
+            .images
+            .read()
+            .map_err(|_| ApiError::internal(anyhow!("image repository lock poisoned")))?;
+        Ok(ids
+            .iter()
+            .filter_map(|id| images.get(id).cloned())
+            .collect())
+    }
+}
@@ -0,0 +1,128 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use anyhow::anyhow;
+use sea_orm::{ActiveValue::Set, DatabaseConnection, EntityTrait, sea_query::OnConflict};
+
+use crate::{
+    error::ApiError, models::project::structure::index_watermark,
+    repositories::project_repository::ProjectWatermark,
+};
+
+/// Where an incremental indexer resumes, and the project it is stuck on, if any.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexWatermark {
+    /// Last project passed; `None` starts from the beginning.
+    pub position: Option<ProjectWatermark>,
+    /// Project right after `position` that failed transiently in the previous job.
+    pub held_project_id: Option<String>,
+    /// Jobs in a row that stopped at `held_project_id`.
+    pub held_attempts: u32,
+}
+
+#[derive(Clone)]
+pub struct IndexWatermarkRepository {
+    db: DatabaseConnection,
+}
+
+impl IndexWatermarkRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn find(&self, name: &str) -> Result<IndexWatermark, ApiError> {
+        let model = index_watermark::Entity::find_by_id(name.to_string())
+            .one(&self.db)
+            .await
+            .map_err(ApiError::database)?;
+
+        Ok(model
+            .map(|model| IndexWatermark {
+                position: model
+                    .updated_at
+                    .zip(model.project_id)
+                    .map(|(updated_at, project_id)| ProjectWatermark {
+                        updated_at,
+                        project_id,
+                    }),
+                held_project_id: model.held_project_id,
+                held_attempts: u32::try_from(model.held_attempts).unwrap_or_default(),
+            })
+            .unwrap_or_default())
+    }
+
+    pub async fn save(&self, name: &str, watermark: &IndexWatermark) -> Result<(), ApiError> {
+        let position = watermark.position.as_ref();
+        let model = index_watermark::ActiveModel {
+            name: Set(name.to_string()),
+            updated_at: Set(position.map(|position| position.updated_at)),
+            project_id: Set(position.map(|position| position.project_id.clone())),
+            held_project_id: Set(watermark.held_project_id.clone()),
+            held_attempts: Set(i32::try_from(watermark.held_attempts).unwrap_or(i32::MAX)),
+        };
+
+        index_watermark::Entity::insert(model)
+            .on_conflict(
+                OnConflict::column(index_watermark::Column::Name)
+                    .update_columns([
+                        index_watermark::Column::UpdatedAt,
+                        index_watermark::Column::ProjectId,
+                        index_watermark::Column::HeldProjectId,
+                        index_watermark::Column::HeldAttempts,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db)
+            .await
+            .map_err(ApiError::database)?;
+
+        Ok(())
+    }
+}
+
+/// Watermarks used when Postgres is not configured; lost on restart.
+#[derive(Default)]
+pub struct InMemoryIndexWatermarkRepository {
+    watermarks: RwLock<HashMap<String, IndexWatermark>>,
+}
+
+impl InMemoryIndexWatermarkRepository {
+    pub fn find(&self, name: &str) -> Result<IndexWatermark, ApiError> {
+        Ok(self
+            .watermarks
+            .read()
+            .map_err(|_| ApiError::internal(anyhow!("watermark repository lock poisoned")))?
+            .get(name)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    pub fn save(&self, name: &str, watermark: &IndexWatermark) -> Result<(), ApiError> {
+        self.watermarks
+            .write()
+            .map_err(|_| ApiError::internal(anyhow!("watermark repository lock poisoned")))?
+            .insert(name.to_string(), watermark.clone());
+        Ok(())
+    }
+}
+
+/// Watermark storage backed by Postgres or, without a database, by process memory.
+pub enum WatermarkStore<'a> {
+    Postgres(&'a IndexWatermarkRepository),
+    InMemory(&'a InMemoryIndexWatermarkRepository),
+}
+
+impl WatermarkStore<'_> {
+    pub async fn find(&self, name: &str) -> Result<IndexWatermark, ApiError> {
+        match self {
+            Self::Postgres(repository) => repository.find(name).await,
+            Self::InMemory(repository) => repository.find(name),
+        }
+    }
+
+    pub async fn save(&self, name: &str, watermark: &IndexWatermark) -> Result<(), ApiError> {
+        match self {
+            Self::Postgres(repository) => repository.save(name, watermark).await,
+            Self::InMemory(repository) => repository.save(name, watermark),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, sync::RwLock, time::Duration};
+
+use anyhow::anyhow;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::NotSet, ColumnTrait, ConnectionTrait, DatabaseBackend,
+    DatabaseConnection, DbErr, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, SqlErr,
+    Statement,
+};
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    models::job::{self, JobKind, JobStatus},
+};
+
+#[derive(Clone)]
+pub struct JobRepository {
+    db: DatabaseConnection,
+}
+
+impl JobRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<job::Model>, ApiError> {
+        job::Entity::find_by_id(id)
+            .one(&self.db)
+            .await
+            .map_err(ApiError::database)
+    }
+
+    /// Queued or running job of `kind`, oldest first.
+    pub async fn find_active(&self, kind: JobKind) -> Result<Option<job::Model>, ApiError> {
+        job::Entity::find()
+            .filter(job::Column::Kind.eq(kind))
+            .filter(job::Column::Status.is_in([JobStatus::Queued, JobStatus::Running]))
+            .order_by_asc(job::Column::CreatedAt)
+            .one(&self.db)
+            .await
+            .map_err(ApiError::database)
+    }
+
+    /// Returns `false` when `job` would be a second active incremental job.
+    pub async fn insert(&self, job: &job::Model) -> Result<bool, ApiError> {
+        match job::Entity::insert(job.clone().into_active_model())
+            .exec(&self.db)
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(err) if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                Ok(false)
+            }
+            Err(err) => Err(ApiError::database(err)),
+        }
+    }
+
+    /// Take a queued job for `owner`; `None` when another instance claimed it first.
+    pub async fn claim(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<Option<job::Model>, ApiError> {
+        let statement = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE jobs SET status = 'running', owner = $1, \
+             lease_expires_at = now() + make_interval(secs => $2) \
+             WHERE id = $3 AND status = 'queued' \
+             RETURNING *",
+            [owner.into(), lease.as_secs_f64().into(), id.into()],
+        );
+        job::Entity::find()
+            .from_raw_sql(statement)
+            .one(&self.db)
+            .await
+            .map_err(ApiError::database)
+    }
+
+    /// Take every queued job and every running job whose owner stopped renewing its lease.
+    ///
+    /// Rows locked by a concurrent claim are skipped, so each job goes to a single instance.
+    pub async fn claim_unfinished(
+        &self,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<Vec<job::Model>, ApiError> {
+        let statement = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE jobs SET status = 'running', owner = $1, \
+             lease_expires_at = now() + make_interval(secs => $2) \
+             WHERE id IN ( \
+                 SELECT id FROM jobs \
+                 WHERE status = 'queued' \
+                    OR (status = 'running' \
+                        AND (lease_expires_at IS NULL OR lease_expires_at < now())) \
+                 ORDER BY created_at \
+                 FOR UPDATE SKIP LOCKED \
+             ) \
+             RETURNING *",
+            [owner.into(), lease.as_secs_f64().into()],
+        );
+        job::Entity::find()
+            .from_raw_sql(statement)
+            .all(&self.db)
+            .await
+            .map_err(ApiError::database)
+    }
+
+    /// Extend the lease of a running job; `false` when `owner` no longer holds it.
+    pub async fn renew_lease(
+        &self,
+        id: Uuid,
+        owner: &str,
+        lease: Duration,
+    ) -> Result<bool, ApiError> {
+        let statement = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE jobs SET lease_expires_at = now() + make_interval(secs => $1) \
+             WHERE id = $2 AND owner = $3 AND status = 'running'",
+            [lease.as_secs_f64().into(), id.into(), owner.into()],
+        );
+        let result = self
+            .db
+            .execute(statement)
+            .await
+            .map_err(ApiError::database)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Write progress; fails once another instance has taken the job over.
+    pub async fn save(&self, job: &job::Model) -> Result<(), ApiError> {
+        // The lease columns are only written by claims and renewals.
+        let mut model = job.clone().into_active_model().reset_all();
+        model.owner = NotSet;
+        model.lease_expires_at = NotSet;
+
+        let mut update = job::Entity::update(model);
+        if let Some(owner) = &job.owner {
+            update = update.filter(job::Column::Owner.eq(owner.as_str()));
+        }
+        match update.exec(&self.db).await {
+            Ok(_) => Ok(()),
+            Err(DbErr::RecordNotUpdated) => Err(ApiError::internal(anyhow!(
+                "job {} was taken over by another instance",
+                job.id
+            ))),
+            Err(err) => Err(ApiError::database(err)),
+        }
+    }
+}
+
+/// Job bookkeeping used when Postgres is not configured; lost on restart.
+#[derive(Default)]
+pub struct InMemoryJobRepository {
+    jobs: RwLock<HashMap<Uuid, job::Model>>,
+}
+
+impl InMemoryJobRepository {
+    pub fn find_by_id(&self, id: Uuid) -> Result<Option<job::Model>, ApiError> {
+        Ok(self.read()?.get(&id).cloned())
+    }
+
+    pub fn find_active(&self, kind: JobKind) -> Result<Option<job::Model>, ApiError> {
+        Ok(self
+            .read()?
+            .values()
+            .filter(|job| job.kind == kind && job.is_active())
+            .min_by_key(|job| job.created_at)
+            .cloned())
+    }
+
+    /// Returns `false` when `job` would be a second active incremental job.
+    pub fn insert(&self, job: &job::Model) -> Result<bool, ApiError> {
+        let mut jobs = self.write()?;
+        if job.kind == JobKind::IncrementalIndex
+            && jobs
+                .values()
+                .any(|active| active.kind == job.kind && active.is_active())
+        {
+            return Ok(false);
+        }
+        jobs.insert(job.id, job.clone());
+        Ok(true)
+    }
+
+    pub fn save(&self, job: &job::Model) -> Result<(), ApiError> {
+        self.write()?.insert(job.id, job.clone());
+        Ok(())
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, HashMap<Uuid, job::Model>>, ApiError> {
+        self.jobs
+            .read()
+            .map_err(|_| ApiError::internal(anyhow!("job repository lock poisoned")))
+    }
+
+    fn write(
+        &self,
+    ) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<Uuid, job::Model>>, ApiError> {
+        self.jobs
+            .write()
+            .map_err(|_| ApiError::internal(anyhow!("job repository lock poisoned")))
+    }
+}
+
+/// Job storage backed by Postgres or, without a database, by process memory.
+pub enum JobStore<'a> {
+    Postgres(&'a JobRepository),
+    InMemory(&'a InMemoryJobRepository),
+}
+
+impl JobStore<'_> {
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<job::Model>, ApiError> {
+        match self {
+            Self::Postgres(repository) => repository.find_by_id(id).await,
+            Self::InMemory(repository) => repository.find_by_id(id),
+        }
+    }
+
+    pub async fn find_active(&self, kind: JobKind) -> Result<Option<job::Model>, ApiError> {
+        match self {
+            Self::Postgres(repository) => repository.find_active(kind).await,
+            Self::InMemory(repository) => repository.find_active(kind),
+        }
+    }
+
+    /// Returns `false` when `job` would be a second active incremental job.
+    pub async fn insert(&self, job: &job::Model) -> Result<bool, ApiError> {
+        match self {
+            Self::Postgres(repository) => repository.insert(job).await,
+            Self::InMemory(repository) => repository.insert(job),
+        }
+    }
+
+    pub async fn save(&self, job: &job::Model) -> Result<(), ApiError> {
+        match self {
+            Self::Postgres(repository) => repository.save(job).await,
+            Self::InMemory(repository) => repository.save(job),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+pub mod http_store;
+pub mod local_store;
+pub mod s3_store;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+pub use http_store::HttpObjectStore;
+pub use local_store::LocalObjectStore;
+pub use s3_store::S3ObjectStore;
+
+/// Validators a backend returned with an object, echoed back on the next `get`.
+#[derive(Clone, Debug, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ObjectMeta {
+    pub key: String,
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+pub enum GetOutcome {
+    Found {
+        body: Vec<u8>,
+        validators: Validators,
+    },
+    NotModified,
+    NotFound,
+}
+
+#[derive(Debug, Error)]
+pub enum ObjectStoreError {
+    /// Connection errors, timeouts, throttling and 5xx responses; worth retrying.
+    #[error("{0}")]
+    Unavailable(String),
+    #[error("{0}")]
+    Failed(String),
+    /// The backend cannot perform the operation at all, e.g. listing through a CDN.
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+impl ObjectStoreError {
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Unavailable(_))
+    }
+}
+
+/// Where project assets (`projects/{id}/...`) live.
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Objects whose key starts with `prefix`.
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, ObjectStoreError>;
+
+    /// Read `key`, answering `NotModified` when `validators` still match.
+    async fn get(
+        &self,
+        key: &str,
+        validators: Option<&Validators>,
+    ) -> Result<GetOutcome, ObjectStoreError>;
+
+    /// Cheap reachability check used by the readiness probe.
+    async fn ping(&self) -> Result<(), ObjectStoreError>;
+}
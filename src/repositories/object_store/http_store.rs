@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use reqwest::{
+    Client as HttpClient, StatusCode,
+    header::{ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+
+use super::{GetOutcome, ObjectMeta, ObjectStore, ObjectStoreError, Validators};
+
+/// Read-only access through the public CDN. A CDN cannot enumerate keys, so `list`
+/// is unsupported.
+pub struct HttpObjectStore {
+    http_client: HttpClient,
+    base_url: String,
+}
+
+impl HttpObjectStore {
+    pub fn new(http_client: HttpClient, base_url: String) -> Self {
+        Self {
+            http_client,
+            base_url,
+        }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!(
+            "{}/{}",
+            self.base_url.trim_end_matches('/'),
+            key.trim_start_matches('/')
+        )
+    }
+}
+
+#[async_trait]
+impl ObjectStore for HttpObjectStore {
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, ObjectStoreError> {
+        Err(ObjectStoreError::Unsupported(format!(
+            "cannot list {prefix}: the CDN object store cannot enumerate keys"
+        )))
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+        validators: Option<&Validators>,
+    ) -> Result<GetOutcome, ObjectStoreError> {
+        let url = self.url(key);
+        let mut request = self.http_client.get(&url);
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request.send().await.map_err(|err| {
+            ObjectStoreError::Unavailable(format!("failed to fetch {url}: {err}"))
+        })?;
+
+        let status = response.status();
+        match status {
+            StatusCode::NOT_MODIFIED if validators.is_some() => {
+                return Ok(GetOutcome::NotModified);
+            }
+            StatusCode::NOT_FOUND => return Ok(GetOutcome::NotFound),
+            _ if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+                return Err(ObjectStoreError::Unavailable(format!(
+                    "failed to fetch {url}: status {status}"
+                )));
+            }
+            _ if !status.is_success() => {
+                return Err(ObjectStoreError::Failed(format!(
+                    "failed to fetch {url}: status {status}"
+                )));
+            }
+            _ => {}
+        }
+
+        let validators = response_validators(response.headers());
+        let body = response
+            .bytes()
+            .await
+            .map_err(|err| ObjectStoreError::Unavailable(format!("failed to read {url}: {err}")))?;
+
+        Ok(GetOutcome::Found {
+            body: body.to_vec(),
+            validators,
+        })
+    }
+
+    async fn ping(&self) -> Result<(), ObjectStoreError> {
+        let response = self
+            .http_client
+            .head(&self.base_url)
+            .send()
+            .await
+            .map_err(|err| {
+                ObjectStoreError::Unavailable(format!("failed to reach {}: {err}", self.base_url))
+            })?;
+
+        // The CDN root need not be servable; only server errors mean it is down.
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(ObjectStoreError::Unavailable(format!(
+                "failed to reach {}: status {status}",
+                self.base_url
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn response_validators(headers: &HeaderMap) -> Validators {
+    let header = |name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+
+    Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    }
+}
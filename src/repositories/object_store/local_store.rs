@@ -0,0 +1,149 @@
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{GetOutcome, ObjectMeta, ObjectStore, ObjectStoreError, Validators};
+
+/// Objects stored as files under `root`, with keys as relative paths.
+pub struct LocalObjectStore {
+    root: PathBuf,
+}
+
+impl LocalObjectStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, ObjectStoreError> {
+        let relative = Path::new(key.trim_start_matches('/'));
+        if relative
+            .components()
+            .any(|component| !matches!(component, std::path::Component::Normal(_)))
+        {
+            return Err(ObjectStoreError::Failed(format!(
+                "invalid object key {key}"
+            )));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ObjectStore for LocalObjectStore {
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, ObjectStoreError> {
+        let prefix = prefix.trim_start_matches('/');
+        // Walk the deepest directory the prefix names, then filter by the full prefix.
+        let directory = match prefix.rfind('/') {
+            Some(index) => self.path(&prefix[..index])?,
+            None => self.root.clone(),
+        };
+
+        let mut objects = Vec::new();
+        let mut pending = vec![directory];
+        while let Some(directory) = pending.pop() {
+            let mut entries = match tokio::fs::read_dir(&directory).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(io_error(&directory, err)),
+            };
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|err| io_error(&directory, err))?
+            {
+                let path = entry.path();
+                let metadata = entry.metadata().await.map_err(|err| io_error(&path, err))?;
+                if metadata.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+
+                let Some(key) = self.key(&path) else {
+                    continue;
+                };
+                if key.starts_with(prefix) {
+                    objects.push(ObjectMeta {
+                        key,
+                        last_modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+                    });
+                }
+            }
+        }
+
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+        validators: Option<&Validators>,
+    ) -> Result<GetOutcome, ObjectStoreError> {
+        let path = self.path(key)?;
+        let metadata = match tokio::fs::metadata(&path).await {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => return Ok(GetOutcome::NotFound),
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(GetOutcome::NotFound),
+            Err(err) => return Err(io_error(&path, err)),
+        };
+
+        let etag = etag(metadata.len(), metadata.modified().ok());
+        if validators.is_some_and(|validators| validators.etag.as_deref() == Some(etag.as_str())) {
+            return Ok(GetOutcome::NotModified);
+        }
+
+        let body = tokio::fs::read(&path)
+            .await
+            .map_err(|err| io_error(&path, err))?;
+
+        Ok(GetOutcome::Found {
+            body,
+            validators: Validators {
+                etag: Some(etag),
+                last_modified: None,
+            },
+        })
+    }
+
+    async fn ping(&self) -> Result<(), ObjectStoreError> {
+        match tokio::fs::metadata(&self.root).await {
+            Ok(metadata) if metadata.is_dir() => Ok(()),
+            Ok(_) => Err(ObjectStoreError::Failed(format!(
+                "{} is not a directory",
+                self.root.display()
+            ))),
+            Err(err) => Err(io_error(&self.root, err)),
+        }
+    }
+}
+
+impl LocalObjectStore {
+    fn key(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        let parts: Vec<_> = relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<_>>()?;
+        Some(parts.join("/"))
+    }
+}
+
+fn etag(len: u64, modified: Option<SystemTime>) -> String {
+    let modified = modified
+        .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!("\"{len:x}-{modified:x}\"")
+}
+
+fn io_error(path: &Path, err: std::io::Error) -> ObjectStoreError {
+    ObjectStoreError::Failed(format!("{}: {err}", path.display()))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use super::LocalObjectStore;
+use crate::repositories::object_store::{GetOutcome, ObjectStore, ObjectStoreError};
+
+/// Store rooted in a fresh temporary directory, removed when dropped.
+struct TempStore {
+    root: PathBuf,
+    store: LocalObjectStore,
+}
+
+impl TempStore {
+    fn new() -> Self {
+        let root = std::env::temp_dir().join(format!("local-object-store-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("temp dir");
+        Self {
+            store: LocalObjectStore::new(root.clone()),
+            root,
+        }
+    }
+}
+
+impl TempStore {
+    /// Write `key` straight to disk, as whatever uploads the objects would.
+    fn write(&self, key: &str, body: &[u8]) {
+        let path = self.root.join(key);
+        std::fs::create_dir_all(path.parent().expect("parent")).expect("parent dirs");
+        std::fs::write(path, body).expect("written");
+    }
+}
+
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+async fn body(store: &LocalObjectStore, key: &str) -> Option<Vec<u8>> {
+    match store.get(key, None).await.expect("readable") {
+        GetOutcome::Found { body, .. } => Some(body),
+        GetOutcome::NotModified => panic!("{key} answered NotModified without validators"),
+        GetOutcome::NotFound => None,
+    }
+}
+
+#[tokio::test]
+async fn lists_only_keys_under_the_prefix() {
+    let temp = TempStore::new();
+    for key in [
+        "projects/p1/images/b.png",
+        "projects/p1/images/a.png",
+        "projects/p1/floorplans.json",
+        "projects/p10/images/c.png",
+    ] {
+        temp.write(key, b"");
+    }
+
+    let keys: Vec<_> = temp
+        .store
+        .list("projects/p1/images")
+        .await
+        .expect("listed")
+        .into_iter()
+        .map(|object| object.key)
+        .collect();
+
+    assert_eq!(
+        keys,
+        ["projects/p1/images/a.png", "projects/p1/images/b.png"]
+    );
+    assert!(
+        temp.store
+            .list("projects/missing/")
+            .await
+            .expect("listed")
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn answers_not_modified_for_a_matching_etag() {
+    let temp = TempStore::new();
+    let key = "projects/p1/floorplans.json";
+    temp.write(key, b"[]");
+
+    let GetOutcome::Found { validators, .. } = temp.store.get(key, None).await.expect("readable")
+    else {
+        panic!("{key} was just written");
+    };
+    assert!(matches!(
+        temp.store.get(key, Some(&validators)).await,
+        Ok(GetOutcome::NotModified)
+    ));
+    assert!(
+        body(&temp.store, "projects/p1/missing.json")
+            .await
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn rejects_keys_that_escape_the_root() {
+    let temp = TempStore::new();
+    let outside = temp.root.parent().expect("parent").join(format!(
+        "{}.json",
+        temp.root.file_name().expect("name").to_string_lossy()
+    ));
+    std::fs::write(&outside, b"[]").expect("written");
+
+    let key = format!(
+        "../{}",
+        outside.file_name().expect("name").to_string_lossy()
+    );
+    let result = temp.store.get(&key, None).await;
+    let _ = std::fs::remove_file(&outside);
+
+    assert!(matches!(result, Err(ObjectStoreError::Failed(_))));
+    assert!(matches!(
+        temp.store.list("../").await,
+        Err(ObjectStoreError::Failed(_))
+    ));
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use aws_sdk_s3::{Client as S3Client, config::http::HttpResponse, error::SdkError};
+use chrono::{DateTime, Utc};
+
+use super::{GetOutcome, ObjectMeta, ObjectStore, ObjectStoreError, Validators};
+
+pub struct S3ObjectStore {
+    client: S3Client,
+    bucket: String,
+}
+
+impl S3ObjectStore {
+    pub fn new(client: S3Client, bucket: String) -> Self {
+        Self { client, bucket }
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3ObjectStore {
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, ObjectStoreError> {
+        let mut objects = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let response = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|err| sdk_error(format!("failed to list {prefix}"), err))?;
+
+            objects.extend(response.contents().iter().filter_map(|object| {
+                Some(ObjectMeta {
+                    key: object.key()?.to_string(),
+                    last_modified: object.last_modified().and_then(|modified| {
+                        DateTime::<Utc>::from_timestamp(modified.secs(), modified.subsec_nanos())
+                    }),
+                })
+            }));
+
+            continuation_token = response.next_continuation_token().map(str::to_string);
+            if continuation_token.is_none() {
+                return Ok(objects);
+            }
+        }
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+        validators: Option<&Validators>,
+    ) -> Result<GetOutcome, ObjectStoreError> {
+        let response = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .set_if_none_match(validators.and_then(|validators| validators.etag.clone()))
+            .send()
+            .await;
+
+        let output = match response {
+            Ok(output) => output,
+            Err(SdkError::ServiceError(err)) if err.err().is_no_such_key() => {
+                return Ok(GetOutcome::NotFound);
+            }
+            Err(SdkError::ServiceError(err)) if err.raw().status().as_u16() == 304 => {
+                return Ok(GetOutcome::NotModified);
+            }
+            Err(err) => return Err(sdk_error(format!("failed to fetch {key}"), err)),
+        };
+
+        let validators = Validators {
+            etag: output.e_tag().map(str::to_string),
+            last_modified: None,
+        };
+        let body =
+            output.body.collect().await.map_err(|err| {
+                ObjectStoreError::Unavailable(format!("failed to read {key}: {err}"))
+            })?;
+
+        Ok(GetOutcome::Found {
+            body: body.to_vec(),
+            validators,
+        })
+    }
+
+    async fn ping(&self) -> Result<(), ObjectStoreError> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(|err| sdk_error(format!("failed to reach bucket {}", self.bucket), err))?;
+        Ok(())
+    }
+}
+
+fn sdk_error<E>(context: String, err: SdkError<E, HttpResponse>) -> ObjectStoreError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let retryable = match &err {
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            true
+        }
+        SdkError::ServiceError(service) => {
+            let status = service.raw().status().as_u16();
+            status >= 500 || status == 429
+        }
+        _ => false,
+    };
+    let message = format!(
+        "{context}: {}",
+        aws_sdk_s3::error::DisplayErrorContext(&err)
+    );
+
+    if retryable {
+        ObjectStoreError::Unavailable(message)
+    } else {
+        ObjectStoreError::Failed(message)
+    }
+}
@@ -1,12 +1,53 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use mongodb::{
     Collection, Database,
-    bson::{Bson, doc},
+    bson::{Bson, DateTime as BsonDateTime, Document, doc},
+    change_stream::{
+        ChangeStream,
+        event::{ChangeStreamEvent, ResumeToken},
+    },
+    options::ChangeStreamOptions,
     options::FindOptions,
     options::UpdateOptions,
 };
 
 use crate::{error::ApiError, models::project::Project};
 
+/// Position of a project in `(updatedAt, _id)` order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProjectWatermark {
+    pub updated_at: DateTime<Utc>,
+    pub project_id: String,
+}
+
+/// Project lookups the handlers and indexing jobs need.
+#[async_trait]
+pub trait ProjectStore: Send + Sync {
+    async fn get_by_id(&self, id: &str) -> Result<Project, ApiError>;
+
+    async fn persist_default_cover_image(
+        &self,
+        project_id: &str,
+        url: &str,
+    ) -> Result<(), ApiError>;
+
+    /// Ids of the `limit` most recently updated projects.
+    async fn find_recent_ids(&self, limit: i64) -> Result<Vec<String>, ApiError>;
+
+    /// Page through projects in ascending `(updatedAt, _id)` order, strictly after `after`.
+    async fn find_changed_since(
+        &self,
+        after: Option<&ProjectWatermark>,
+        limit: i64,
+    ) -> Result<Vec<ProjectWatermark>, ApiError>;
+
+    async fn find_many_by_ids(&self, ids: &[String]) -> Result<Vec<Project>, ApiError>;
+}
+
 #[derive(Clone)]
 pub struct ProjectRepository {
     collection: Collection<Project>,
@@ -19,18 +60,44 @@ impl ProjectRepository {
         }
     }
 
-    pub async fn get_by_id(&self, id: &str) -> Result<Project, ApiError> {
+    /// Open a change stream of project inserts, updates, replacements and deletions.
+    ///
+    /// Events only carry the document key; callers reload the project when they need it.
+    /// Errors are returned as-is so callers can tell a lost resume point from an outage.
+    pub async fn watch_changes(
+        &self,
+        resume_after: Option<ResumeToken>,
+    ) -> mongodb::error::Result<ChangeStream<ChangeStreamEvent<Document>>> {
+        let pipeline = [doc! {
+            "$match": {
+                "operationType": { "$in": ["insert", "update", "replace", "delete"] }
+            }
+        }];
+        let options = ChangeStreamOptions::builder()
+            .resume_after(resume_after)
+            .build();
+
+        self.collection
+            .clone_with_type::<Document>()
+            .watch(pipeline, options)
+            .await
+    }
+}
+
+#[async_trait]
+impl ProjectStore for ProjectRepository {
+    async fn get_by_id(&self, id: &str) -> Result<Project, ApiError> {
         let project = self
             .collection
             .find_one(doc! { "_id": id }, None)
             .await
-            .map_err(ApiError::internal)?
+            .map_err(ApiError::mongo)?
             .ok_or_else(|| ApiError::not_found(format!("Project {id} not found")))?;
 
         Ok(project)
     }
 
-    pub async fn persist_default_cover_image(
+    async fn persist_default_cover_image(
         &self,
         project_id: &str,
         url: &str,
@@ -42,11 +109,11 @@ impl ProjectRepository {
                 UpdateOptions::builder().upsert(false).build(),
             )
             .await
-            .map_err(ApiError::internal)?;
+            .map_err(ApiError::mongo)?;
         Ok(())
     }
 
-    pub async fn find_recent_ids(&self, limit: i64) -> Result<Vec<String>, ApiError> {
+    async fn find_recent_ids(&self, limit: i64) -> Result<Vec<String>, ApiError> {
         if limit <= 0 {
             return Ok(Vec::new());
         }
@@ -60,11 +127,11 @@ impl ProjectRepository {
             .collection
             .find(doc! {}, options)
             .await
-            .map_err(ApiError::internal)?;
+            .map_err(ApiError::mongo)?;
 
         let mut ids = Vec::new();
-        while cursor.advance().await.map_err(ApiError::internal)? {
-            let project: Project = cursor.deserialize_current().map_err(ApiError::internal)?;
+        while cursor.advance().await.map_err(ApiError::mongo)? {
+            let project: Project = cursor.deserialize_current().map_err(ApiError::mongo)?;
 
             if let Some(id) = project.id {
                 ids.push(id);
@@ -74,7 +141,68 @@ impl ProjectRepository {
         Ok(ids)
     }
 
-    pub async fn find_many_by_ids(&self, ids: &[String]) -> Result<Vec<Project>, ApiError> {
+    async fn find_changed_since(
+        &self,
+        after: Option<&ProjectWatermark>,
+        limit: i64,
+    ) -> Result<Vec<ProjectWatermark>, ApiError> {
+        if limit <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let filter = match after {
+            Some(watermark) => {
+                let updated_at = BsonDateTime::from_chrono(watermark.updated_at);
+                doc! {
+                    "$or": [
+                        { "updatedAt": { "$gt": updated_at } },
+                        { "updatedAt": updated_at, "_id": { "$gt": &watermark.project_id } },
+                    ]
+                }
+            }
+            None => doc! { "updatedAt": { "$type": "date" } },
+        };
+
+        let options = FindOptions::builder()
+            .sort(doc! { "updatedAt": 1, "_id": 1 })
+            .projection(doc! { "_id": 1, "updatedAt": 1 })
+            .limit(limit)
+            .build();
+
+        let mut cursor = self
+            .collection
+            .clone_with_type::<Document>()
+            .find(filter, options)
+            .await
+            .map_err(ApiError::mongo)?;
+
+        let mut watermarks = Vec::new();
+        while cursor.advance().await.map_err(ApiError::mongo)? {
+            let document = cursor.deserialize_current().map_err(ApiError::mongo)?;
+            let (project_id, updated_at) =
+                match (document.get_str("_id"), document.get_datetime("updatedAt")) {
+                    (Ok(project_id), Ok(updated_at)) => (project_id, updated_at),
+                    (project_id, updated_at) => {
+                        tracing::warn!(
+                            id = ?document.get("_id"),
+                            id_error = project_id.err().map(tracing::field::display),
+                            updated_at_error = updated_at.err().map(tracing::field::display),
+                            "skipping changed project without a string id and a date updatedAt"
+                        );
+                        continue;
+                    }
+                };
+
+            watermarks.push(ProjectWatermark {
+                updated_at: updated_at.to_chrono(),
+                project_id: project_id.to_string(),
+            });
+        }
+
+        Ok(watermarks)
+    }
+
+    async fn find_many_by_ids(&self, ids: &[String]) -> Result<Vec<Project>, ApiError> {
         if ids.is_empty() {
             return Ok(Vec::new());
         }
@@ -86,14 +214,120 @@ impl ProjectRepository {
             .collection
             .find(filter, None)
             .await
-            .map_err(ApiError::internal)?;
+            .map_err(ApiError::mongo)?;
 
         let mut projects = Vec::new();
-        while cursor.advance().await.map_err(ApiError::internal)? {
-            let project: Project = cursor.deserialize_current().map_err(ApiError::internal)?;
+        while cursor.advance().await.map_err(ApiError::mongo)? {
+            let project: Project = cursor.deserialize_current().map_err(ApiError::mongo)?;
             projects.push(project);
         }
 
         Ok(projects)
     }
 }
+
+/// Projects held in process memory, e.g. loaded from a fixture directory.
+#[derive(Default)]
+pub struct InMemoryProjectRepository {
+    projects: RwLock<HashMap<String, Project>>,
+}
+
+impl InMemoryProjectRepository {
+    pub fn insert(&self, project: Project) -> Result<(), ApiError> {
+        let id = project
+            .id
+            .clone()
+            .ok_or_else(|| ApiError::internal(anyhow!("project is missing _id")))?;
+        self.write()?.insert(id, project);
+        Ok(())
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, HashMap<String, Project>>, ApiError> {
+        self.projects
+            .read()
+            .map_err(|_| ApiError::internal(anyhow!("project repository lock poisoned")))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<String, Project>>, ApiError> {
+        self.projects
+            .write()
+            .map_err(|_| ApiError::internal(anyhow!("project repository lock poisoned")))
+    }
+
+    fn watermarks(&self) -> Result<Vec<ProjectWatermark>, ApiError> {
+        let mut watermarks: Vec<ProjectWatermark> = self
+            .read()?
+            .iter()
+            .filter_map(|(id, project)| {
+                Some(ProjectWatermark {
+                    updated_at: project.updated_at?.to_chrono(),
+                    project_id: id.clone(),
+                })
+            })
+            .collect();
+        watermarks
+            .sort_by(|a, b| (a.updated_at, &a.project_id).cmp(&(b.updated_at, &b.project_id)));
+        Ok(watermarks)
+    }
+}
+
+#[async_trait]
+impl ProjectStore for InMemoryProjectRepository {
+    async fn get_by_id(&self, id: &str) -> Result<Project, ApiError> {
+        self.read()?
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ApiError::not_found(format!("Project {id} not found")))
+    }
+
+    async fn persist_default_cover_image(
+        &self,
+        project_id: &str,
+        url: &str,
+    ) -> Result<(), ApiError> {
+        if let Some(project) = self.write()?.get_mut(project_id) {
+            project.default_cover_image = Some(url.to_string());
+        }
+        Ok(())
+    }
+
+    async fn find_recent_ids(&self, limit: i64) -> Result<Vec<String>, ApiError> {
+        let limit = usize::try_from(limit).unwrap_or_default();
+        let mut projects: Vec<_> = self
+            .read()?
+            .iter()
+            .map(|(id, project)| (project.updated_at, id.clone()))
+            .collect();
+        // Newest first; projects that were never updated sort last, as in Mongo.
+        projects.sort_by(|a, b| b.cmp(a));
+
+        Ok(projects.into_iter().take(limit).map(|(_, id)| id).collect())
+    }
+
+    async fn find_changed_since(
+        &self,
+        after: Option<&ProjectWatermark>,
+        limit: i64,
+    ) -> Result<Vec<ProjectWatermark>, ApiError> {
+        let limit = usize::try_from(limit).unwrap_or_default();
+        Ok(self
+            .watermarks()?
+            .into_iter()
+            .filter(|watermark| {
+                after.is_none_or(|after| {
+                    (watermark.updated_at, &watermark.project_id)
+                        > (after.updated_at, &after.project_id)
+                })
+            })
+            .take(limit)
+            .collect())
+    }
+
+    async fn find_many_by_ids(&self, ids: &[String]) -> Result<Vec<Project>, ApiError> {
+        let projects = self.read()?;
+        Ok(ids
+            .iter()
+            .filter_map(|id| projects.get(id).cloned())
+            .collect())
+    }
+}
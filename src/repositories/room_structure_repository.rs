@@ -1,18 +1,31 @@
+use anyhow::anyhow;
 use sea_orm::{
+    ActiveModelTrait,
     ActiveValue::Set,
-    ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait, FromQueryResult, Order,
-    Statement,
-    sea_query::{Alias, Expr, ExprTrait, Func, OnConflict, Query},
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    FromQueryResult, Iterable, Order, QueryFilter, Statement, TransactionTrait,
+    sea_query::{
+        Alias, Asterisk, Expr, ExprTrait, Func, IntoIden, OnConflict, Query, SelectStatement,
+        SimpleExpr,
+    },
 };
 
 use crate::{
     error::ApiError,
     models::project::structure::{RoomStructureColumn, RoomStructureEntity, room_structure},
+    repositories::structure_store::{DEFAULT_UPSERT_CHUNK_SIZE, WriteSummary, clamp_chunk_size},
+    scoring::{
+        RoomScoring,
+        embedding::{room_embedding, to_vector_literal},
+    },
+    utils::cursor::{SimilarityCursor, remaining_candidates},
 };
 
 #[derive(Clone)]
 pub struct RoomStructureRepository {
     db: DatabaseConnection,
+    ann_candidates: Option<u64>,
+    chunk_size: usize,
 }
 
 #[allow(dead_code)]
@@ -21,7 +34,17 @@ pub struct SimilarRoom {
     pub id: String,
     pub project_id: String,
     pub area: f64,
+    pub bounding_box_aspect: f64,
+    pub rectangularity: f64,
+    pub compactness: Option<f64>,
+    pub convexity: Option<f64>,
     pub score: f64,
+    pub area_distance: f64,
+    pub aspect_distance: f64,
+    pub rectangularity_distance: f64,
+    pub compactness_distance: f64,
+    pub convexity_distance: f64,
+    pub outline_distance: f64,
 }
 
 #[derive(Clone, Debug)]
@@ -35,6 +58,11 @@ pub struct RoomStructureRecord {
     pub bounding_box_area: f64,
     pub bounding_box_aspect: f64,
     pub rectangularity: f64,
+    pub polygon_area: Option<f64>,
+    pub perimeter: Option<f64>,
+    pub convexity: Option<f64>,
+    pub compactness: Option<f64>,
+    pub shape_descriptor: Option<Vec<f64>>,
 }
 
 impl From<RoomStructureRecord> for room_structure::ActiveModel {
@@ -49,20 +77,65 @@ impl From<RoomStructureRecord> for room_structure::ActiveModel {
             bounding_box_area: Set(record.bounding_box_area),
             bounding_box_aspect: Set(record.bounding_box_aspect),
             rectangularity: Set(record.rectangularity),
+            polygon_area: Set(record.polygon_area),
+            perimeter: Set(record.perimeter),
+            convexity: Set(record.convexity),
+            compactness: Set(record.compactness),
+            shape_descriptor: Set(record.shape_descriptor),
+        }
+    }
+}
+
+impl From<RoomStructureRecord> for room_structure::Model {
+    fn from(record: RoomStructureRecord) -> Self {
+        room_structure::Model {
+            id: record.id,
+            project_id: record.project_id,
+            r#type: record.r#type,
+            area: record.area,
+            bounding_box_width: record.bounding_box_width,
+            bounding_box_depth: record.bounding_box_depth,
+            bounding_box_area: record.bounding_box_area,
+            bounding_box_aspect: record.bounding_box_aspect,
+            rectangularity: record.rectangularity,
+            polygon_area: record.polygon_area,
+            perimeter: record.perimeter,
+            convexity: record.convexity,
+            compactness: record.compactness,
+            shape_descriptor: record.shape_descriptor,
         }
     }
 }
 
 impl RoomStructureRepository {
     pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+        Self {
+            db,
+            ann_candidates: None,
+            chunk_size: DEFAULT_UPSERT_CHUNK_SIZE,
+        }
+    }
+
+    /// Rows written per multi-row upsert statement, at most what fits the bind parameter limit.
+    pub fn with_chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        if let Some(chunk_size) = chunk_size.filter(|chunk_size| *chunk_size > 0) {
+            // Every column is bound, plus the embedding.
+            self.chunk_size = clamp_chunk_size(chunk_size, RoomStructureColumn::iter().count() + 1);
+        }
+        self
+    }
+
+    /// Rank only the `candidates` nearest rooms by embedding instead of scanning the table.
+    pub fn with_ann_candidates(mut self, candidates: Option<u64>) -> Self {
+        self.ann_candidates = candidates.filter(|candidates| *candidates > 0);
+        self
     }
 
     pub async fn find_by_id(&self, id: &str) -> Result<Option<room_structure::Model>, ApiError> {
         RoomStructureEntity::find_by_id(id.to_string())
             .one(&self.db)
             .await
-            .map_err(ApiError::internal)
+            .map_err(ApiError::database)
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -74,12 +147,28 @@ impl RoomStructureRepository {
         area_to: f64,
         rectangularity: f64,
         aspect: f64,
+        compactness: Option<f64>,
+        convexity: Option<f64>,
+        shape_descriptor: Option<&[f64]>,
         room_type: i32,
+        scoring: &RoomScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
     ) -> Result<Vec<SimilarRoom>, ApiError> {
+        let k = remaining_candidates(self.ann_candidates, k, after);
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        // The outline area is exact; rows indexed before it was stored fall back to `area`.
         let area_dist = Func::abs(
-            Expr::col((room_structure::Entity, RoomStructureColumn::Area)).sub(Expr::value(area)),
+            Expr::expr(Func::coalesce([
+                Expr::col((room_structure::Entity, RoomStructureColumn::PolygonArea)).into(),
+                Expr::col((room_structure::Entity, RoomStructureColumn::Area)).into(),
+            ]))
+            .sub(Expr::value(area)),
         )
-        .div(Expr::value(area.max(5.0_f64)));
+        .div(Expr::value(area.max(scoring.min_area_normalizer)));
         let aspect_dist = Func::abs(
             Expr::col((
                 room_structure::Entity,
@@ -91,12 +180,60 @@ impl RoomStructureRepository {
             Expr::col((room_structure::Entity, RoomStructureColumn::Rectangularity))
                 .sub(Expr::value(rectangularity)),
         );
+        // Rows indexed before shape metrics existed get the maximum compactness distance.
+        let compactness_dist: SimpleExpr = match compactness {
+            Some(compactness) => Func::coalesce([
+                Func::abs(
+                    Expr::col((room_structure::Entity, RoomStructureColumn::Compactness))
+                        .sub(Expr::value(compactness)),
+                )
+                .into(),
+                Expr::value(1.0_f64),
+            ])
+            .into(),
+            None => Expr::value(0.0_f64),
+        };
+        let convexity_dist: SimpleExpr = match convexity {
+            Some(convexity) => Func::coalesce([
+                Func::abs(
+                    Expr::col((room_structure::Entity, RoomStructureColumn::Convexity))
+                        .sub(Expr::value(convexity)),
+                )
+                .into(),
+                Expr::value(1.0_f64),
+            ])
+            .into(),
+            None => Expr::value(0.0_f64),
+        };
+        let outline_dist = match shape_descriptor {
+            Some(descriptor) => outline_distance(descriptor),
+            None => Expr::value(0.0_f64),
+        };
 
         let score_expr = area_dist
             .clone()
-            .mul(Expr::value(0.3_f64))
-            .add(aspect_dist.clone().mul(Expr::value(0.5_f64)))
-            .add(rectangularity_dist.clone().mul(Expr::value(0.2_f64)));
+            .mul(Expr::value(scoring.area_weight))
+            .add(aspect_dist.clone().mul(Expr::value(scoring.aspect_weight)))
+            .add(
+                rectangularity_dist
+                    .clone()
+                    .mul(Expr::value(scoring.rectangularity_weight)),
+            )
+            .add(
+                compactness_dist
+                    .clone()
+                    .mul(Expr::value(scoring.compactness_weight)),
+            )
+            .add(
+                convexity_dist
+                    .clone()
+                    .mul(Expr::value(scoring.convexity_weight)),
+            )
+            .add(
+                outline_dist
+                    .clone()
+                    .mul(Expr::value(scoring.outline_weight)),
+            );
 
         let score_alias = Alias::new("score");
 
@@ -105,8 +242,20 @@ impl RoomStructureRepository {
             .column((room_structure::Entity, RoomStructureColumn::Id))
             .column((room_structure::Entity, RoomStructureColumn::ProjectId))
             .column((room_structure::Entity, RoomStructureColumn::Area))
+            .column((
+                room_structure::Entity,
+                RoomStructureColumn::BoundingBoxAspect,
+            ))
+            .column((room_structure::Entity, RoomStructureColumn::Rectangularity))
+            .column((room_structure::Entity, RoomStructureColumn::Compactness))
+            .column((room_structure::Entity, RoomStructureColumn::Convexity))
             .expr_as(score_expr.clone(), score_alias.clone())
-            .from(room_structure::Entity)
+            .expr_as(area_dist, Alias::new("area_distance"))
+            .expr_as(aspect_dist, Alias::new("aspect_distance"))
+            .expr_as(rectangularity_dist, Alias::new("rectangularity_distance"))
+            .expr_as(compactness_dist, Alias::new("compactness_distance"))
+            .expr_as(convexity_dist, Alias::new("convexity_distance"))
+            .expr_as(outline_dist, Alias::new("outline_distance"))
             .and_where(
                 Expr::col((room_structure::Entity, RoomStructureColumn::ProjectId))
                     .ne(exclude_project_id),
@@ -121,14 +270,14 @@ impl RoomStructureRepository {
                     RoomStructureColumn::BoundingBoxAspect,
                 ))
                 .between(
-                    Expr::value(aspect * 0.85_f64),
-                    Expr::value(aspect * 1.15_f64),
+                    Expr::value(aspect * (1.0 - scoring.aspect_tolerance)),
+                    Expr::value(aspect * (1.0 + scoring.aspect_tolerance)),
                 ),
             )
             .and_where(
                 Expr::col((room_structure::Entity, RoomStructureColumn::Rectangularity)).between(
-                    Expr::value(rectangularity - 0.1_f64),
-                    Expr::value(rectangularity + 0.1_f64),
+                    Expr::value(rectangularity - scoring.rectangularity_tolerance),
+                    Expr::value(rectangularity + scoring.rectangularity_tolerance),
                 ),
             );
 
@@ -138,7 +287,44 @@ impl RoomStructureRepository {
             );
         }
 
-        select.order_by(score_alias.clone(), Order::Asc);
+        // The band filters above act as post-filters on the approximate neighbours.
+        match self.ann_candidates {
+            Some(candidates) => select.from_subquery(
+                nearest_rooms(
+                    exclude_project_id,
+                    room_type,
+                    &room_embedding(
+                        area,
+                        aspect,
+                        rectangularity,
+                        compactness,
+                        convexity,
+                        shape_descriptor,
+                    ),
+                    candidates,
+                ),
+                room_structure::Entity,
+            ),
+            None => select.from(room_structure::Entity),
+        };
+
+        if let Some(cursor) = after {
+            let id_col = Expr::col((room_structure::Entity, RoomStructureColumn::Id));
+            select.and_where(
+                score_expr.clone().gt(cursor.score).or(score_expr
+                    .clone()
+                    .eq(cursor.score)
+                    .and(id_col.gt(cursor.id.as_str()))),
+            );
+        }
+
+        select
+            .order_by(score_alias.clone(), Order::Asc)
+            .order_by(
+                (room_structure::Entity, RoomStructureColumn::Id),
+                Order::Asc,
+            )
+            .limit(k);
 
         let backend: DatabaseBackend = self.db.get_database_backend();
         let stmt: Statement = backend.build(&select);
@@ -146,36 +332,171 @@ impl RoomStructureRepository {
         SimilarRoom::find_by_statement(stmt)
             .all(&self.db)
             .await
-            .map_err(ApiError::internal)
+            .map_err(ApiError::database)
+    }
+
+    /// Replace every room of `project_id` with `records`, dropping rooms that no longer exist.
+    pub async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<RoomStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        let txn = self.db.begin().await.map_err(ApiError::database)?;
+        let summary = self.replace_in(&txn, project_id, records).await?;
+        txn.commit().await.map_err(ApiError::database)?;
+
+        Ok(summary)
     }
 
-    pub async fn save_all(&self, records: Vec<RoomStructureRecord>) -> Result<(), ApiError> {
-        if records.is_empty() {
-            return Ok(());
+    /// [`Self::replace_by_project_id`] on a caller-owned connection or transaction.
+    pub async fn replace_in<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        project_id: &str,
+        records: Vec<RoomStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        let ids: Vec<String> = records.iter().map(|record| record.id.clone()).collect();
+
+        let deleted = RoomStructureEntity::delete_many()
+            .filter(RoomStructureColumn::ProjectId.eq(project_id))
+            .filter(RoomStructureColumn::Id.is_not_in(ids))
+            .exec(db)
+            .await
+            .map_err(ApiError::database)?;
+
+        let mut summary = upsert_rooms(db, &records, self.chunk_size).await?;
+        summary.removed = deleted.rows_affected;
+
+        Ok(summary)
+    }
+}
+
+/// Upsert `records` with one multi-row statement per chunk, embeddings included.
+///
+/// `xmax = 0` only holds for freshly inserted tuples, which tells inserts and updates apart.
+async fn upsert_rooms<C: ConnectionTrait>(
+    db: &C,
+    records: &[RoomStructureRecord],
+    chunk_size: usize,
+) -> Result<WriteSummary, ApiError> {
+    let mut summary = WriteSummary::default();
+
+    for chunk in records.chunks(chunk_size.max(1)) {
+        let mut insert = Query::insert();
+        insert
+            .into_table(room_structure::Entity)
+            .columns(
+                RoomStructureColumn::iter()
+                    .map(IntoIden::into_iden)
+                    .chain([Alias::new("embedding").into_iden()]),
+            )
+            .on_conflict(
+                OnConflict::column(RoomStructureColumn::Id)
+                    .update_columns(
+                        RoomStructureColumn::iter()
+                            .filter(|column| !matches!(column, RoomStructureColumn::Id)),
+                    )
+                    .update_column(Alias::new("embedding"))
+                    .to_owned(),
+            )
+            .returning(Query::returning().expr(Expr::cust("xmax = 0")));
+
+        for record in chunk {
+            let embedding = room_embedding(
+                record.polygon_area.unwrap_or(record.area),
+                record.bounding_box_aspect,
+                record.rectangularity,
+                record.compactness,
+                record.convexity,
+                record.shape_descriptor.as_deref(),
+            );
+            let model: room_structure::ActiveModel = record.clone().into();
+            let mut values = RoomStructureColumn::iter()
+                .map(|column| model.get(column).into_value().map(Expr::value))
+                .collect::<Option<Vec<SimpleExpr>>>()
+                .ok_or_else(|| {
+                    ApiError::internal(anyhow!("room {} has unset columns", record.id))
+                })?;
+            values.push(Expr::cust_with_exprs(
+                "$1::vector",
+                [Expr::value(to_vector_literal(&embedding))],
+            ));
+            insert.values(values).map_err(ApiError::internal)?;
         }
 
-        for record in records {
-            let model: room_structure::ActiveModel = record.into();
-            RoomStructureEntity::insert(model)
-                .on_conflict(
-                    OnConflict::column(RoomStructureColumn::Id)
-                        .update_columns([
-                            RoomStructureColumn::ProjectId,
-                            RoomStructureColumn::Type,
-                            RoomStructureColumn::Area,
-                            RoomStructureColumn::BoundingBoxWidth,
-                            RoomStructureColumn::BoundingBoxDepth,
-                            RoomStructureColumn::BoundingBoxArea,
-                            RoomStructureColumn::BoundingBoxAspect,
-                            RoomStructureColumn::Rectangularity,
-                        ])
-                        .to_owned(),
-                )
-                .exec(&self.db)
-                .await
-                .map_err(ApiError::internal)?;
+        let rows = db
+            .query_all(DatabaseBackend::Postgres.build(&insert))
+            .await
+            .map_err(ApiError::database)?;
+        for row in rows {
+            if row
+                .try_get_by_index::<bool>(0)
+                .map_err(ApiError::database)?
+            {
+                summary.inserted += 1;
+            } else {
+                summary.updated += 1;
+            }
         }
+    }
 
-        Ok(())
+    Ok(summary)
+}
+
+/// Approximate nearest neighbours of `embedding`, served by the HNSW index on `rooms.embedding`.
+///
+/// The room type is filtered here rather than on the candidates, so rooms of other types do not
+/// use up the candidate pool.
+fn nearest_rooms(
+    exclude_project_id: &str,
+    room_type: i32,
+    embedding: &[f64],
+    candidates: u64,
+) -> SelectStatement {
+    let mut select = Query::select();
+    select
+        .column(Asterisk)
+        .from(room_structure::Entity)
+        .and_where(
+            Expr::col((room_structure::Entity, RoomStructureColumn::ProjectId))
+                .ne(exclude_project_id),
+        )
+        .and_where(Expr::col((room_structure::Entity, Alias::new("embedding"))).is_not_null());
+    if room_type != 0 {
+        select.and_where(
+            Expr::col((room_structure::Entity, RoomStructureColumn::Type)).eq(room_type),
+        );
     }
+
+    select
+        .order_by_expr(
+            Expr::cust_with_exprs(
+                "$1 <-> $2::vector",
+                [
+                    Expr::col((room_structure::Entity, Alias::new("embedding"))).into(),
+                    Expr::value(to_vector_literal(embedding)),
+                ],
+            ),
+            Order::Asc,
+        )
+        .limit(candidates)
+        .to_owned()
+}
+
+/// [`descriptor_distance`](crate::geometry::descriptor_distance) between the stored and the query outline descriptors; `UNNEST` pads
+/// the shorter array with NULLs, read as zero.
+///
+/// Rooms without a stored descriptor get a distance of `1.0`.
+fn outline_distance(descriptor: &[f64]) -> SimpleExpr {
+    let stored = || Expr::col((room_structure::Entity, RoomStructureColumn::ShapeDescriptor));
+    Expr::cust_with_exprs(
+        "CASE WHEN $1 IS NULL THEN 1.0 ELSE \
+            (SELECT COALESCE(SQRT(SUM(POWER(COALESCE(a, 0) - COALESCE(b, 0), 2))), 0) \
+            FROM UNNEST($2, $3::float8[]) AS t(a, b)) END",
+        [
+            stored().into(),
+            stored().into(),
+            Expr::value(descriptor.to_vec()),
+        ],
+    )
 }
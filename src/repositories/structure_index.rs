@@ -0,0 +1,471 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    error::ApiError,
+    geometry::descriptor_distance,
+    models::project::{
+        child::structure::GraphSignature,
+        structure::{floor_structure, room_structure},
+    },
+    repositories::{
+        floor_structure_repository::{FloorStructureRecord, SimilarFloor},
+        room_structure_repository::{RoomStructureRecord, SimilarRoom},
+        structure_store::WriteSummary,
+    },
+    scoring::{FloorScoring, RoomScoring},
+    utils::cursor::SimilarityCursor,
+};
+
+/// In-process floor and room index used when Postgres is not configured.
+///
+/// Scores candidates exactly like the SQL path of [`FloorStructureRepository`] and
+/// [`RoomStructureRepository`], and optionally persists itself to a JSON snapshot after every
+/// write.
+///
+/// [`FloorStructureRepository`]: crate::repositories::floor_structure_repository::FloorStructureRepository
+/// [`RoomStructureRepository`]: crate::repositories::room_structure_repository::RoomStructureRepository
+pub struct StructureIndex {
+    data: RwLock<Snapshot>,
+    snapshot_path: Option<PathBuf>,
+    /// Held while writing the snapshot so concurrent writes cannot interleave.
+    persist_lock: Mutex<()>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Snapshot {
+    floors: HashMap<String, IndexedFloor>,
+    rooms: HashMap<String, room_structure::Model>,
+}
+
+/// A floor with its graph signature decoded once, when the floor enters the index.
+///
+/// Snapshots hold the model alone; the signature is decoded again when they are loaded.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "floor_structure::Model")]
+struct IndexedFloor {
+    #[serde(flatten)]
+    model: floor_structure::Model,
+    #[serde(skip)]
+    graph_signature: Option<GraphSignature>,
+}
+
+impl From<FloorStructureRecord> for IndexedFloor {
+    fn from(record: FloorStructureRecord) -> Self {
+        let graph_signature = record.graph_signature.clone();
+        Self {
+            model: record.into(),
+            graph_signature,
+        }
+    }
+}
+
+impl From<floor_structure::Model> for IndexedFloor {
+    fn from(model: floor_structure::Model) -> Self {
+        let graph_signature = model.decode_graph_signature();
+        Self {
+            model,
+            graph_signature,
+        }
+    }
+}
+
+impl StructureIndex {
+    /// Open the index, loading `snapshot_path` when it exists.
+    pub fn open(snapshot_path: Option<PathBuf>) -> anyhow::Result<Self> {
+        let data = match snapshot_path.as_deref() {
+            Some(path) if path.exists() => load_snapshot(path)?,
+            _ => Snapshot::default(),
+        };
+
+        Ok(Self {
+            data: RwLock::new(data),
+            snapshot_path,
+            persist_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read()
+            .map(|data| data.floors.is_empty() && data.rooms.is_empty())
+            .unwrap_or(true)
+    }
+
+    pub fn find_floor_by_id(&self, id: &str) -> Result<Option<floor_structure::Model>, ApiError> {
+        Ok(self.read()?.floors.get(id).map(|floor| floor.model.clone()))
+    }
+
+    pub fn find_room_by_id(&self, id: &str) -> Result<Option<room_structure::Model>, ApiError> {
+        Ok(self.read()?.rooms.get(id).cloned())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_top_k_similar_floors(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        room_count: i32,
+        area_from: f64,
+        area_to: f64,
+        aspect: f64,
+        rectangularity: f64,
+        graph_signature: Option<&GraphSignature>,
+        scoring: &FloorScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarFloor>, ApiError> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let data = self.read()?;
+        let mut best_per_project: HashMap<&str, SimilarFloor> = HashMap::new();
+
+        for IndexedFloor {
+            model: floor,
+            graph_signature: candidate_signature,
+        } in data.floors.values()
+        {
+            if floor.project_id == exclude_project_id
+                || !within(floor.area, area_from, area_to)
+                || !within(
+                    floor.bounding_box_aspect,
+                    aspect * (1.0 - scoring.aspect_tolerance),
+                    aspect * (1.0 + scoring.aspect_tolerance),
+                )
+                || !within(
+                    floor.rectangularity,
+                    rectangularity - scoring.rectangularity_tolerance,
+                    rectangularity + scoring.rectangularity_tolerance,
+                )
+                || !within(
+                    f64::from(floor.room_count),
+                    f64::from(room_count - scoring.room_count_tolerance),
+                    f64::from(room_count + scoring.room_count_tolerance),
+                )
+            {
+                continue;
+            }
+
+            let area_distance = (floor.area - area).abs() / area.max(scoring.min_area_normalizer);
+            let aspect_distance = (floor.bounding_box_aspect - aspect).abs();
+            let rectangularity_distance = (floor.rectangularity - rectangularity).abs();
+            let room_count_diff = f64::from(floor.room_count - room_count).abs();
+            let room_count_distance =
+                room_count_diff / (room_count_diff + f64::from(room_count.max(1)));
+            let topology_distance = graph_signature
+                .map(|signature| signature.distance(candidate_signature.as_ref()))
+                .unwrap_or_default();
+
+            let score = area_distance * scoring.area_weight
+                + aspect_distance * scoring.aspect_weight
+                + rectangularity_distance * scoring.rectangularity_weight
+                + room_count_distance * scoring.room_count_weight
+                + topology_distance * scoring.topology_weight;
+
+            let candidate = SimilarFloor {
+                id: floor.id.clone(),
+                title: floor.title.clone(),
+                project_id: floor.project_id.clone(),
+                area: floor.area,
+                room_count: floor.room_count,
+                bounding_box_aspect: floor.bounding_box_aspect,
+                rectangularity: floor.rectangularity,
+                score,
+                area_distance,
+                aspect_distance,
+                rectangularity_distance,
+                room_count_distance,
+                topology_distance,
+            };
+
+            match best_per_project.get(floor.project_id.as_str()) {
+                Some(current)
+                    if !ranks_before(
+                        candidate.score,
+                        &candidate.id,
+                        current.score,
+                        &current.id,
+                    ) => {}
+                _ => {
+                    best_per_project.insert(floor.project_id.as_str(), candidate);
+                }
+            }
+        }
+
+        let results = best_per_project.into_values().collect();
+        Ok(page(results, |floor| (floor.score, &floor.id), k, after))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_similar_rooms(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        area_from: f64,
+        area_to: f64,
+        rectangularity: f64,
+        aspect: f64,
+        compactness: Option<f64>,
+        convexity: Option<f64>,
+        shape_descriptor: Option<&[f64]>,
+        room_type: i32,
+        scoring: &RoomScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarRoom>, ApiError> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let data = self.read()?;
+        let mut results = Vec::new();
+
+        for room in data.rooms.values() {
+            if room.project_id == exclude_project_id
+                || !within(room.area, area_from, area_to)
+                || !within(
+                    room.bounding_box_aspect,
+                    aspect * (1.0 - scoring.aspect_tolerance),
+                    aspect * (1.0 + scoring.aspect_tolerance),
+                )
+                || !within(
+                    room.rectangularity,
+                    rectangularity - scoring.rectangularity_tolerance,
+                    rectangularity + scoring.rectangularity_tolerance,
+                )
+                || (room_type != 0 && room.r#type != room_type)
+            {
+                continue;
+            }
+
+            let area_distance = (room.polygon_area.unwrap_or(room.area) - area).abs()
+                / area.max(scoring.min_area_normalizer);
+            let aspect_distance = (room.bounding_box_aspect - aspect).abs();
+            let rectangularity_distance = (room.rectangularity - rectangularity).abs();
+            let compactness_distance = compactness
+                .map(|query| {
+                    room.compactness
+                        .map(|candidate| (candidate - query).abs())
+                        .unwrap_or(1.0)
+                })
+                .unwrap_or_default();
+            let convexity_distance = convexity
+                .map(|query| {
+                    room.convexity
+                        .map(|candidate| (candidate - query).abs())
+                        .unwrap_or(1.0)
+                })
+                .unwrap_or_default();
+            let outline_distance = shape_descriptor
+                .map(|query| {
+                    room.shape_descriptor
+                        .as_deref()
+                        .map(|candidate| descriptor_distance(candidate, query))
+                        .unwrap_or(1.0)
+                })
+                .unwrap_or_default();
+
+            let score = area_distance * scoring.area_weight
+                + aspect_distance * scoring.aspect_weight
+                + rectangularity_distance * scoring.rectangularity_weight
+                + compactness_distance * scoring.compactness_weight
+                + convexity_distance * scoring.convexity_weight
+                + outline_distance * scoring.outline_weight;
+
+            results.push(SimilarRoom {
+                id: room.id.clone(),
+                project_id: room.project_id.clone(),
+                area: room.area,
+                bounding_box_aspect: room.bounding_box_aspect,
+                rectangularity: room.rectangularity,
+                compactness: room.compactness,
+                convexity: room.convexity,
+                score,
+                area_distance,
+                aspect_distance,
+                rectangularity_distance,
+                compactness_distance,
+                convexity_distance,
+                outline_distance,
+            });
+        }
+
+        Ok(page(results, |room| (room.score, &room.id), k, after))
+    }
+
+    pub async fn replace_floors_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<FloorStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        let mut summary = WriteSummary::default();
+        {
+            let mut data = self.write()?;
+            let before = data.floors.len();
+            data.floors.retain(|id, floor| {
+                floor.model.project_id != project_id
+                    || records.iter().any(|record| &record.id == id)
+            });
+            summary.removed = (before - data.floors.len()) as u64;
+
+            for record in records {
+                let floor = IndexedFloor::from(record);
+                match data.floors.insert(floor.model.id.clone(), floor) {
+                    Some(_) => summary.updated += 1,
+                    None => summary.inserted += 1,
+                }
+            }
+        }
+
+        self.persist().await?;
+        Ok(summary)
+    }
+
+    pub async fn replace_rooms_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<RoomStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        let mut summary = WriteSummary::default();
+        {
+            let mut data = self.write()?;
+            let before = data.rooms.len();
+            data.rooms.retain(|id, model| {
+                model.project_id != project_id || records.iter().any(|record| &record.id == id)
+            });
+            summary.removed = (before - data.rooms.len()) as u64;
+
+            for record in records {
+                match data.rooms.insert(record.id.clone(), record.into()) {
+                    Some(_) => summary.updated += 1,
+                    None => summary.inserted += 1,
+                }
+            }
+        }
+
+        self.persist().await?;
+        Ok(summary)
+    }
+
+    /// Write the current contents to the snapshot file, if one is configured.
+    ///
+    /// Writes are serialized and the contents are read once the lock is held, so the last
+    /// writer always leaves the newest state on disk.
+    pub async fn persist(&self) -> Result<(), ApiError> {
+        let Some(path) = self.snapshot_path.as_deref() else {
+            return Ok(());
+        };
+
+        let _guard = self.persist_lock.lock().await;
+        let payload = serde_json::to_vec(&*self.read()?).map_err(ApiError::internal)?;
+        let tmp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&tmp_path, payload)
+            .await
+            .with_context(|| format!("failed to write snapshot {}", tmp_path.display()))
+            .map_err(ApiError::internal)?;
+        tokio::fs::rename(&tmp_path, path)
+            .await
+            .with_context(|| format!("failed to replace snapshot {}", path.display()))
+            .map_err(ApiError::internal)?;
+
+        Ok(())
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Snapshot>, ApiError> {
+        self.data
+            .read()
+            .map_err(|_| ApiError::internal(anyhow!("structure index lock poisoned")))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Snapshot>, ApiError> {
+        self.data
+            .write()
+            .map_err(|_| ApiError::internal(anyhow!("structure index lock poisoned")))
+    }
+}
+
+/// [`Snapshot`] with every record left undecoded.
+#[derive(Deserialize)]
+struct RawSnapshot {
+    floors: HashMap<String, Value>,
+    rooms: HashMap<String, Value>,
+}
+
+/// Records are decoded one by one, so a record that cannot be read back, e.g. a non-finite
+/// value written as `null`, is dropped instead of failing startup.
+fn load_snapshot(path: &Path) -> anyhow::Result<Snapshot> {
+    let payload = std::fs::read(path)
+        .with_context(|| format!("failed to read snapshot {}", path.display()))?;
+    let raw: RawSnapshot = serde_json::from_slice(&payload)
+        .with_context(|| format!("failed to parse snapshot {}", path.display()))?;
+
+    Ok(Snapshot {
+        floors: decode_records("floor", raw.floors),
+        rooms: decode_records("room", raw.rooms),
+    })
+}
+
+fn decode_records<T: DeserializeOwned>(
+    kind: &str,
+    records: HashMap<String, Value>,
+) -> HashMap<String, T> {
+    records
+        .into_iter()
+        .filter_map(|(id, record)| match serde_json::from_value(record) {
+            Ok(record) => Some((id, record)),
+            Err(err) => {
+                tracing::warn!(id, error = %err, "dropping unreadable {kind} from the snapshot");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Inclusive range check matching SQL `BETWEEN`.
+fn within(value: f64, from: f64, to: f64) -> bool {
+    from <= value && value <= to
+}
+
+fn ranks_before(score: f64, id: &str, other_score: f64, other_id: &str) -> bool {
+    match score.total_cmp(&other_score) {
+        std::cmp::Ordering::Equal => id < other_id,
+        ordering => ordering.is_lt(),
+    }
+}
+
+/// Order by `(score, id)`, skip everything up to `after` and keep the first `k` results.
+fn page<T>(
+    mut results: Vec<T>,
+    key: impl Fn(&T) -> (f64, &String),
+    k: u64,
+    after: Option<&SimilarityCursor>,
+) -> Vec<T> {
+    results.sort_by(|a, b| {
+        let ((a_score, a_id), (b_score, b_id)) = (key(a), key(b));
+        a_score.total_cmp(&b_score).then_with(|| a_id.cmp(b_id))
+    });
+
+    results
+        .into_iter()
+        .filter(|result| {
+            after.is_none_or(|cursor| {
+                let (score, id) = key(result);
+                ranks_before(cursor.score, &cursor.id, score, id)
+            })
+        })
+        .take(k as usize)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
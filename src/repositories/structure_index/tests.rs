@@ -0,0 +1,319 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use uuid::Uuid;
+
+use super::{StructureIndex, page};
+use crate::{
+    models::project::child::structure::GraphSignature,
+    repositories::{
+        floor_structure_repository::FloorStructureRecord,
+        room_structure_repository::RoomStructureRecord,
+    },
+    scoring::{FloorScoring, RoomScoring},
+    utils::cursor::SimilarityCursor,
+};
+
+/// Snapshot path in a fresh temporary directory, removed when dropped.
+struct TempSnapshot(PathBuf);
+
+impl TempSnapshot {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("structure-index-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("temp dir");
+        Self(dir.join("index.json"))
+    }
+}
+
+impl Drop for TempSnapshot {
+    fn drop(&mut self) {
+        if let Some(dir) = self.0.parent() {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn floor(project_id: &str) -> FloorStructureRecord {
+    FloorStructureRecord {
+        id: format!("{project_id}_floor"),
+        title: format!("Floor of {project_id}"),
+        project_id: project_id.to_string(),
+        area: 60.0,
+        room_count: 2,
+        bounding_box_width: 10_000.0,
+        bounding_box_depth: 6_000.0,
+        bounding_box_area: 60_000_000.0,
+        bounding_box_aspect: 10.0 / 6.0,
+        rectangularity: 0.9,
+        graph_signature: None,
+    }
+}
+
+fn room(project_id: &str) -> RoomStructureRecord {
+    RoomStructureRecord {
+        id: format!("{project_id}_room"),
+        project_id: project_id.to_string(),
+        r#type: 1,
+        area: 24_000_000.0,
+        bounding_box_width: 6_000.0,
+        bounding_box_depth: 4_000.0,
+        bounding_box_area: 24_000_000.0,
+        bounding_box_aspect: 1.5,
+        rectangularity: 1.0,
+        polygon_area: Some(6_000_000.0),
+        perimeter: Some(10_000.0),
+        convexity: Some(1.0),
+        compactness: Some(0.75),
+        shape_descriptor: Some(vec![1.0, 0.5, 0.25]),
+    }
+}
+
+#[tokio::test]
+async fn snapshot_round_trips() {
+    let snapshot = TempSnapshot::new();
+    let index = StructureIndex::open(Some(snapshot.0.clone())).expect("open");
+    index
+        .replace_floors_by_project_id("project-a", vec![floor("project-a")])
+        .await
+        .expect("floors saved");
+    index
+        .replace_rooms_by_project_id("project-a", vec![room("project-a")])
+        .await
+        .expect("rooms saved");
+
+    let reloaded = StructureIndex::open(Some(snapshot.0.clone())).expect("reopen");
+    assert_eq!(
+        reloaded.find_floor_by_id("project-a_floor").expect("read"),
+        index.find_floor_by_id("project-a_floor").expect("read")
+    );
+    assert_eq!(
+        reloaded.find_room_by_id("project-a_room").expect("read"),
+        index.find_room_by_id("project-a_room").expect("read")
+    );
+    assert!(
+        reloaded
+            .find_room_by_id("project-a_room")
+            .expect("read")
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn reopens_a_snapshot_holding_a_zero_breadth_room() {
+    let snapshot = TempSnapshot::new();
+    let index = StructureIndex::open(Some(snapshot.0.clone())).expect("open");
+    let mut flat = room("project-b");
+    flat.bounding_box_depth = 0.0;
+    flat.bounding_box_area = 0.0;
+    flat.bounding_box_aspect = f64::INFINITY;
+    index
+        .replace_rooms_by_project_id("project-a", vec![room("project-a")])
+        .await
+        .expect("rooms saved");
+    index
+        .replace_rooms_by_project_id("project-b", vec![flat])
+        .await
+        .expect("rooms saved");
+
+    let reloaded = StructureIndex::open(Some(snapshot.0.clone())).expect("reopen");
+    assert!(
+        reloaded
+            .find_room_by_id("project-a_room")
+            .expect("read")
+            .is_some()
+    );
+    assert_eq!(
+        reloaded.find_room_by_id("project-b_room").expect("read"),
+        None
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_writes_leave_the_latest_snapshot() {
+    let snapshot = TempSnapshot::new();
+    let index = Arc::new(StructureIndex::open(Some(snapshot.0.clone())).expect("open"));
+
+    let writers: Vec<_> = (0..16)
+        .map(|n| {
+            let index = index.clone();
+            tokio::spawn(async move {
+                let project_id = format!("project-{n}");
+                index
+                    .replace_floors_by_project_id(&project_id, vec![floor(&project_id)])
+                    .await
+                    .expect("floors saved");
+                index
+                    .replace_rooms_by_project_id(&project_id, vec![room(&project_id)])
+                    .await
+                    .expect("rooms saved");
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.await.expect("writer finished");
+    }
+
+    let reloaded = StructureIndex::open(Some(snapshot.0.clone())).expect("reopen");
+    for n in 0..16 {
+        assert!(
+            reloaded
+                .find_floor_by_id(&format!("project-{n}_floor"))
+                .expect("read")
+                .is_some()
+        );
+        assert!(
+            reloaded
+                .find_room_by_id(&format!("project-{n}_room"))
+                .expect("read")
+                .is_some()
+        );
+    }
+    let leftovers: Vec<_> = std::fs::read_dir(snapshot.0.parent().expect("dir"))
+        .expect("readable dir")
+        .map(|entry| entry.expect("entry").file_name())
+        .filter(|name| name != "index.json")
+        .collect();
+    assert!(leftovers.is_empty(), "temporary files left: {leftovers:?}");
+}
+
+#[test]
+fn pages_resume_strictly_after_the_cursor() {
+    let results = vec![
+        (0.2, "c".to_string()),
+        (0.1, "b".to_string()),
+        (0.2, "a".to_string()),
+        (0.1, "a".to_string()),
+        (0.3, "a".to_string()),
+    ];
+    fn key(result: &(f64, String)) -> (f64, &String) {
+        (result.0, &result.1)
+    }
+
+    let first = page(results.clone(), key, 2, None);
+    assert_eq!(first, [(0.1, "a".to_string()), (0.1, "b".to_string())]);
+
+    // Ties on the score continue by id, and the cursor position itself is not repeated.
+    let after = SimilarityCursor::new(0.1, "b", 2);
+    let second = page(results.clone(), key, 2, Some(&after));
+    assert_eq!(second, [(0.2, "a".to_string()), (0.2, "c".to_string())]);
+
+    let after = SimilarityCursor::new(0.2, "b", 3);
+    let third = page(results, key, 2, Some(&after));
+    assert_eq!(third, [(0.2, "c".to_string()), (0.3, "a".to_string())]);
+}
+
+#[tokio::test]
+async fn ranks_rooms_by_convexity_and_compactness() {
+    let index = StructureIndex::open(None).expect("open");
+    let rectangle = room("project-rectangle");
+    // Same bounding box, area and rectangularity band; only the outline differs.
+    let notched = RoomStructureRecord {
+        convexity: Some(0.8),
+        compactness: Some(0.55),
+        ..room("project-notched")
+    };
+    for record in [rectangle, notched] {
+        index
+            .replace_rooms_by_project_id(&record.project_id.clone(), vec![record])
+            .await
+            .expect("rooms saved");
+    }
+
+    let ranking = |compactness: f64, convexity: f64| {
+        index
+            .find_similar_rooms(
+                "project-query",
+                6_000_000.0,
+                0.0,
+                f64::MAX,
+                1.0,
+                1.5,
+                Some(compactness),
+                Some(convexity),
+                None,
+                0,
+                &RoomScoring::default(),
+                10,
+                None,
+            )
+            .expect("query runs")
+            .into_iter()
+            .map(|room| room.project_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ranking(0.75, 1.0), ["project-rectangle", "project-notched"]);
+    assert_eq!(ranking(0.55, 0.8), ["project-notched", "project-rectangle"]);
+}
+
+/// Signature of a floor whose rooms have the given types, all adjacent to the first one.
+fn signature(room_types: &[&str]) -> GraphSignature {
+    let mut signature = GraphSignature::default();
+    for room_type in room_types {
+        *signature
+            .room_types
+            .entry(room_type.to_string())
+            .or_default() += 1;
+    }
+    signature.adjacencies = room_types[1..]
+        .iter()
+        .map(|room_type| (format!("{}-{room_type}", room_types[0]), 1))
+        .collect::<BTreeMap<_, _>>();
+    signature
+}
+
+#[tokio::test]
+async fn ranks_floors_by_layout_after_a_reload() {
+    let snapshot = TempSnapshot::new();
+    let index = StructureIndex::open(Some(snapshot.0.clone())).expect("open");
+    let open_plan = FloorStructureRecord {
+        graph_signature: Some(signature(&["1", "2"])),
+        ..floor("project-open")
+    };
+    let split = FloorStructureRecord {
+        graph_signature: Some(signature(&["1", "3"])),
+        ..floor("project-split")
+    };
+    for record in [open_plan, split] {
+        index
+            .replace_floors_by_project_id(&record.project_id.clone(), vec![record])
+            .await
+            .expect("floors saved");
+    }
+
+    let ranking = |index: &StructureIndex, room_types: &[&str]| {
+        index
+            .find_top_k_similar_floors(
+                "project-query",
+                60.0,
+                2,
+                0.0,
+                f64::MAX,
+                10.0 / 6.0,
+                0.9,
+                Some(&signature(room_types)),
+                &FloorScoring::default(),
+                10,
+                None,
+            )
+            .expect("query runs")
+            .into_iter()
+            .map(|floor| floor.project_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        ranking(&index, &["1", "2"]),
+        ["project-open", "project-split"]
+    );
+    assert_eq!(
+        ranking(&index, &["1", "3"]),
+        ["project-split", "project-open"]
+    );
+
+    // Signatures are decoded again from the snapshot.
+    let reopened = StructureIndex::open(Some(snapshot.0.clone())).expect("reopen");
+    assert_eq!(
+        ranking(&reopened, &["1", "3"]),
+        ["project-split", "project-open"]
+    );
+}
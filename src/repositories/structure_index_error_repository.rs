@@ -0,0 +1,42 @@
+use chrono::Utc;
+use sea_orm::{ActiveValue::Set, DatabaseConnection, EntityTrait};
+use uuid::Uuid;
+
+use crate::{
+    error::{ApiError, IndexFailure},
+    models::project::structure::index_error,
+};
+
+#[derive(Clone)]
+pub struct StructureIndexErrorRepository {
+    db: DatabaseConnection,
+}
+
+impl StructureIndexErrorRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn insert(
+        &self,
+        project_id: &str,
+        job_id: Option<Uuid>,
+        failure: &IndexFailure,
+    ) -> Result<(), ApiError> {
+        let model = index_error::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project_id.to_string()),
+            job_id: Set(job_id),
+            reason: Set(failure.reason),
+            message: Set(failure.message.clone()),
+            created_at: Set(Utc::now()),
+        };
+
+        index_error::Entity::insert(model)
+            .exec(&self.db)
+            .await
+            .map_err(ApiError::database)?;
+
+        Ok(())
+    }
+}
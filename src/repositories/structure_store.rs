@@ -0,0 +1,360 @@
+use std::ops::AddAssign;
+
+use async_trait::async_trait;
+use sea_orm::TransactionTrait;
+use serde::Serialize;
+
+use crate::{
+    error::ApiError,
+    models::project::{
+        child::structure::GraphSignature,
+        structure::{floor_structure, room_structure},
+    },
+    repositories::{
+        floor_structure_repository::{
+            FloorStructureRecord, FloorStructureRepository, SimilarFloor,
+        },
+        room_structure_repository::{RoomStructureRecord, RoomStructureRepository, SimilarRoom},
+        structure_index::StructureIndex,
+    },
+    scoring::{FloorScoring, RoomScoring},
+    utils::cursor::SimilarityCursor,
+};
+
+/// Rows per multi-row upsert statement unless configured otherwise.
+pub const DEFAULT_UPSERT_CHUNK_SIZE: usize = 500;
+/// Bind parameters Postgres accepts in a single statement.
+const MAX_BIND_PARAMS: usize = 65_535;
+
+/// Clamp a configured chunk size so a chunk of rows binding `params_per_row` values each stays
+/// within the Postgres bind parameter limit.
+pub fn clamp_chunk_size(chunk_size: usize, params_per_row: usize) -> usize {
+    let max = MAX_BIND_PARAMS / params_per_row.max(1);
+    if chunk_size > max {
+        tracing::warn!(
+            chunk_size,
+            max,
+            "upsert chunk size exceeds the bind parameter limit"
+        );
+    }
+    chunk_size.clamp(1, max)
+}
+
+/// Row counts of a structure write.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct WriteSummary {
+    pub inserted: u64,
+    pub updated: u64,
+    pub removed: u64,
+}
+
+impl AddAssign for WriteSummary {
+    fn add_assign(&mut self, other: Self) {
+        self.inserted += other.inserted;
+        self.updated += other.updated;
+        self.removed += other.removed;
+    }
+}
+
+/// Floor structure storage, backed by Postgres or, without a database, by the in-process index.
+#[async_trait]
+pub trait FloorStructureStore: Send + Sync {
+    async fn find_by_id(&self, id: &str) -> Result<Option<floor_structure::Model>, ApiError>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn find_top_k_similar_floors(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        room_count: i32,
+        area_from: f64,
+        area_to: f64,
+        aspect: f64,
+        rectangularity: f64,
+        graph_signature: Option<&GraphSignature>,
+        scoring: &FloorScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarFloor>, ApiError>;
+
+    async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<FloorStructureRecord>,
+    ) -> Result<WriteSummary, ApiError>;
+
+    /// The Postgres repository behind this store, for writes that span both structure tables.
+    fn postgres(&self) -> Option<&FloorStructureRepository> {
+        None
+    }
+}
+
+/// Room structure storage, backed by Postgres or, without a database, by the in-process index.
+#[async_trait]
+pub trait RoomStructureStore: Send + Sync {
+    async fn find_by_id(&self, id: &str) -> Result<Option<room_structure::Model>, ApiError>;
+
+    #[allow(clippy::too_many_arguments)]
+    async fn find_similar_rooms(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        area_from: f64,
+        area_to: f64,
+        rectangularity: f64,
+        aspect: f64,
+        compactness: Option<f64>,
+        convexity: Option<f64>,
+        shape_descriptor: Option<&[f64]>,
+        room_type: i32,
+        scoring: &RoomScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarRoom>, ApiError>;
+
+    async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<RoomStructureRecord>,
+    ) -> Result<WriteSummary, ApiError>;
+
+    /// The Postgres repository behind this store, for writes that span both structure tables.
+    fn postgres(&self) -> Option<&RoomStructureRepository> {
+        None
+    }
+}
+
+#[async_trait]
+impl FloorStructureStore for FloorStructureRepository {
+    async fn find_by_id(&self, id: &str) -> Result<Option<floor_structure::Model>, ApiError> {
+        FloorStructureRepository::find_by_id(self, id).await
+    }
+
+    async fn find_top_k_similar_floors(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        room_count: i32,
+        area_from: f64,
+        area_to: f64,
+        aspect: f64,
+        rectangularity: f64,
+        graph_signature: Option<&GraphSignature>,
+        scoring: &FloorScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarFloor>, ApiError> {
+        FloorStructureRepository::find_top_k_similar_floors(
+            self,
+            exclude_project_id,
+            area,
+            room_count,
+            area_from,
+            area_to,
+            aspect,
+            rectangularity,
+            graph_signature,
+            scoring,
+            k,
+            after,
+        )
+        .await
+    }
+
+    async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<FloorStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        FloorStructureRepository::replace_by_project_id(self, project_id, records).await
+    }
+
+    fn postgres(&self) -> Option<&FloorStructureRepository> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl RoomStructureStore for RoomStructureRepository {
+    async fn find_by_id(&self, id: &str) -> Result<Option<room_structure::Model>, ApiError> {
+        RoomStructureRepository::find_by_id(self, id).await
+    }
+
+    async fn find_similar_rooms(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        area_from: f64,
+        area_to: f64,
+        rectangularity: f64,
+        aspect: f64,
+        compactness: Option<f64>,
+        convexity: Option<f64>,
+        shape_descriptor: Option<&[f64]>,
+        room_type: i32,
+        scoring: &RoomScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarRoom>, ApiError> {
+        RoomStructureRepository::find_similar_rooms(
+            self,
+            exclude_project_id,
+            area,
+            area_from,
+            area_to,
+            rectangularity,
+            aspect,
+            compactness,
+            convexity,
+            shape_descriptor,
+            room_type,
+            scoring,
+            k,
+            after,
+        )
+        .await
+    }
+
+    async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<RoomStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        RoomStructureRepository::replace_by_project_id(self, project_id, records).await
+    }
+
+    fn postgres(&self) -> Option<&RoomStructureRepository> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl FloorStructureStore for StructureIndex {
+    async fn find_by_id(&self, id: &str) -> Result<Option<floor_structure::Model>, ApiError> {
+        self.find_floor_by_id(id)
+    }
+
+    async fn find_top_k_similar_floors(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        room_count: i32,
+        area_from: f64,
+        area_to: f64,
+        aspect: f64,
+        rectangularity: f64,
+        graph_signature: Option<&GraphSignature>,
+        scoring: &FloorScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarFloor>, ApiError> {
+        StructureIndex::find_top_k_similar_floors(
+            self,
+            exclude_project_id,
+            area,
+            room_count,
+            area_from,
+            area_to,
+            aspect,
+            rectangularity,
+            graph_signature,
+            scoring,
+            k,
+            after,
+        )
+    }
+
+    async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<FloorStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        self.replace_floors_by_project_id(project_id, records).await
+    }
+}
+
+#[async_trait]
+impl RoomStructureStore for StructureIndex {
+    async fn find_by_id(&self, id: &str) -> Result<Option<room_structure::Model>, ApiError> {
+        self.find_room_by_id(id)
+    }
+
+    async fn find_similar_rooms(
+        &self,
+        exclude_project_id: &str,
+        area: f64,
+        area_from: f64,
+        area_to: f64,
+        rectangularity: f64,
+        aspect: f64,
+        compactness: Option<f64>,
+        convexity: Option<f64>,
+        shape_descriptor: Option<&[f64]>,
+        room_type: i32,
+        scoring: &RoomScoring,
+        k: u64,
+        after: Option<&SimilarityCursor>,
+    ) -> Result<Vec<SimilarRoom>, ApiError> {
+        StructureIndex::find_similar_rooms(
+            self,
+            exclude_project_id,
+            area,
+            area_from,
+            area_to,
+            rectangularity,
+            aspect,
+            compactness,
+            convexity,
+            shape_descriptor,
+            room_type,
+            scoring,
+            k,
+            after,
+        )
+    }
+
+    async fn replace_by_project_id(
+        &self,
+        project_id: &str,
+        records: Vec<RoomStructureRecord>,
+    ) -> Result<WriteSummary, ApiError> {
+        self.replace_rooms_by_project_id(project_id, records).await
+    }
+}
+
+/// Replace the floors and rooms of `project_id` together.
+///
+/// With Postgres behind both stores the two replacements share one transaction, so a failure
+/// never leaves a project with new floors but stale rooms.
+pub async fn replace_project_structures(
+    floors: &dyn FloorStructureStore,
+    rooms: &dyn RoomStructureStore,
+    project_id: &str,
+    floor_records: Vec<FloorStructureRecord>,
+    room_records: Vec<RoomStructureRecord>,
+) -> Result<(WriteSummary, WriteSummary), ApiError> {
+    if let (Some(floors), Some(rooms)) = (floors.postgres(), rooms.postgres()) {
+        let txn = floors
+            .connection()
+            .begin()
+            .await
+            .map_err(ApiError::database)?;
+        let floor_summary = floors.replace_in(&txn, project_id, floor_records).await?;
+        let room_summary = rooms.replace_in(&txn, project_id, room_records).await?;
+        txn.commit().await.map_err(ApiError::database)?;
+
+        return Ok((floor_summary, room_summary));
+    }
+
+    let floor_summary = floors
+        .replace_by_project_id(project_id, floor_records)
+        .await?;
+    let room_summary = rooms
+        .replace_by_project_id(project_id, room_records)
+        .await?;
+
+    Ok((floor_summary, room_summary))
+}
+
+#[cfg(test)]
+mod tests;
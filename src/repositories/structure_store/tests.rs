@@ -0,0 +1,15 @@
+use sea_orm::Iterable;
+
+use super::{MAX_BIND_PARAMS, clamp_chunk_size};
+use crate::models::project::structure::room_structure;
+
+#[test]
+fn chunks_fit_the_bind_parameter_limit() {
+    let params_per_row = room_structure::Column::iter().count() + 1;
+    let chunk_size = clamp_chunk_size(100_000, params_per_row);
+
+    assert!(chunk_size * params_per_row <= MAX_BIND_PARAMS);
+    assert!((chunk_size + 1) * params_per_row > MAX_BIND_PARAMS);
+    assert_eq!(clamp_chunk_size(500, params_per_row), 500);
+    assert_eq!(clamp_chunk_size(0, params_per_row), 1);
+}
@@ -2,8 +2,18 @@ use axum::Router;
 
 use crate::state::AppState;
 
+pub mod health;
+pub mod job;
+pub mod metrics;
 pub mod project;
 
+#[cfg(test)]
+mod tests;
+
 pub fn app_router() -> Router<AppState> {
-    Router::new().merge(project::router())
+    Router::new()
+        .merge(project::router())
+        .merge(job::router())
+        .merge(metrics::router())
+        .merge(health::router())
 }
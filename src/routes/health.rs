@@ -0,0 +1,3 @@
+pub mod health_router;
+
+pub use health_router::router;
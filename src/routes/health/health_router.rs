@@ -0,0 +1,12 @@
+use axum::{Router, routing::get};
+
+use crate::{
+    handlers::health_handler::{get_health, get_readiness},
+    state::AppState,
+};
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/healthz", get(get_health))
+        .route("/readyz", get(get_readiness))
+}
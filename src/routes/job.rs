@@ -0,0 +1,4 @@
+pub mod dto;
+pub mod job_router;
+
+pub use job_router::router;
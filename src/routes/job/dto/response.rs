@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::{
+    job::{self, JobFailure, JobKind, JobStatus},
+    project::structure::index_error::IndexFailureReason,
+};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobResponse {
+    pub id: Uuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub total: i32,
+    pub processed: i32,
+    pub failures: Vec<JobFailure>,
+    /// Number of failed projects per reason.
+    pub failure_counts: BTreeMap<IndexFailureReason, usize>,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub summary: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Milliseconds since the job started, or its total run time once finished.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<i64>,
+}
+
+impl JobResponse {
+    pub fn try_from_job(job: job::Model) -> Result<Self> {
+        let elapsed_ms = job.started_at.map(|started_at| {
+            (job.finished_at.unwrap_or_else(Utc::now) - started_at).num_milliseconds()
+        });
+        let failures: Vec<JobFailure> = serde_json::from_value(job.failures)
+            .with_context(|| format!("job {} has malformed failures", job.id))?;
+        let mut failure_counts = BTreeMap::new();
+        for failure in &failures {
+            *failure_counts.entry(failure.reason).or_default() += 1;
+        }
+
+        Ok(Self {
+            id: job.id,
+            kind: job.kind,
+            status: job.status,
+            total: job.total,
+            processed: job.processed,
+            failures,
+            failure_counts,
+            summary: job.summary,
+            error: job.error,
+            created_at: job.created_at,
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+            elapsed_ms,
+        })
+    }
+}
@@ -0,0 +1,7 @@
+use axum::{Router, routing::get};
+
+use crate::{handlers::job_handler::get_job, state::AppState};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/jobs/{job_id}", get(get_job))
+}
@@ -0,0 +1,3 @@
+pub mod metrics_router;
+
+pub use metrics_router::router;
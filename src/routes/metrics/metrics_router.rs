@@ -0,0 +1,7 @@
+use axum::{Router, routing::get};
+
+use crate::{handlers::metrics_handler::get_metrics, state::AppState};
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/metrics", get(get_metrics))
+}
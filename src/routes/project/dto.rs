@@ -1,6 +1,10 @@
+pub mod request;
 pub mod response;
 
+pub use request::SimilarRoomRequest;
+
 pub use response::{
-    FloorResponse, ProjectRenderingImageResponse, ProjectRenderingsResponse, ProjectResponse,
-    RoomItemsResponse, RoomResponse,
+    FloorResponse, FloorplanValidationResponse, PageResponse, ProjectRenderingImageResponse,
+    ProjectRenderingsResponse, ProjectResponse, RoomItemsResponse, RoomResponse,
+    SimilarityExplanation, StructureIndexResponse,
 };
@@ -0,0 +1,21 @@
+use serde::Deserialize;
+
+/// A point of an ad-hoc room outline, in the same half-scale x/z plane as floorplan corners.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct OutlinePoint {
+    pub x: f64,
+    pub z: f64,
+}
+
+/// Body of `POST /rooms/similar`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarRoomRequest {
+    pub outline: Vec<OutlinePoint>,
+    /// Room type to match; `0` or absent matches every type.
+    #[serde(default)]
+    pub r#type: Option<i32>,
+    /// Room area in m²; defaults to the area enclosed by `outline`, scaled like `room.area`.
+    #[serde(default)]
+    pub area: Option<f64>,
+}
@@ -1,15 +1,23 @@
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::AddAssign};
 
 use crate::models::{
     image::Image as ProjectImage,
     project::{
         Project,
-        child::floorplan::{Floorplan, Item, Room},
+        child::{
+            FloorplanIssue,
+            floorplan::{Floorplan, Item, Room},
+        },
+        structure::{floor_structure, room_structure},
     },
 };
+use crate::repositories::{
+    floor_structure_repository::SimilarFloor, room_structure_repository::SimilarRoom,
+    structure_store::WriteSummary,
+};
 use crate::utils::image::convert_image_url;
 
 #[derive(Debug, Serialize)]
@@ -101,6 +109,8 @@ pub struct RoomResponse {
     pub created_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<SimilarityExplanation>,
 }
 
 impl RoomResponse {
@@ -146,6 +156,7 @@ impl RoomResponse {
             state: project.state,
             created_at,
             updated_at,
+            explanation: None,
         })
     }
 }
@@ -171,6 +182,8 @@ pub struct FloorResponse {
     pub created_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<SimilarityExplanation>,
 }
 
 impl FloorResponse {
@@ -218,10 +231,137 @@ impl FloorResponse {
             state: project.state,
             created_at,
             updated_at,
+            explanation: None,
         })
     }
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityExplanation {
+    pub score: f64,
+    pub distances: SimilarityDistances,
+    pub query: StructureFeatures,
+    pub candidate: StructureFeatures,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarityDistances {
+    pub area: f64,
+    pub aspect: f64,
+    pub rectangularity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_count: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compactness: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub convexity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outline: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureFeatures {
+    pub area: f64,
+    pub bounding_box_aspect: f64,
+    pub rectangularity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_count: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compactness: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub convexity: Option<f64>,
+}
+
+impl SimilarityExplanation {
+    pub fn from_floor(query: &floor_structure::Model, candidate: &SimilarFloor) -> Self {
+        Self {
+            score: candidate.score,
+            distances: SimilarityDistances {
+                area: candidate.area_distance,
+                aspect: candidate.aspect_distance,
+                rectangularity: candidate.rectangularity_distance,
+                room_count: Some(candidate.room_count_distance),
+                compactness: None,
+                convexity: None,
+                topology: query
+                    .graph_signature
+                    .as_ref()
+                    .map(|_| candidate.topology_distance),
+                outline: None,
+            },
+            query: StructureFeatures {
+                area: query.area,
+                bounding_box_aspect: query.bounding_box_aspect,
+                rectangularity: query.rectangularity,
+                room_count: Some(query.room_count),
+                compactness: None,
+                convexity: None,
+            },
+            candidate: StructureFeatures {
+                area: candidate.area,
+                bounding_box_aspect: candidate.bounding_box_aspect,
+                rectangularity: candidate.rectangularity,
+                room_count: Some(candidate.room_count),
+                compactness: None,
+                convexity: None,
+            },
+        }
+    }
+
+    pub fn from_room(query: &room_structure::Model, candidate: &SimilarRoom) -> Self {
+        Self {
+            score: candidate.score,
+            distances: SimilarityDistances {
+                area: candidate.area_distance,
+                aspect: candidate.aspect_distance,
+                rectangularity: candidate.rectangularity_distance,
+                room_count: None,
+                compactness: query.compactness.map(|_| candidate.compactness_distance),
+                convexity: query.convexity.map(|_| candidate.convexity_distance),
+                topology: None,
+                outline: query
+                    .shape_descriptor
+                    .as_ref()
+                    .map(|_| candidate.outline_distance),
+            },
+            query: StructureFeatures {
+                area: query.area,
+                bounding_box_aspect: query.bounding_box_aspect,
+                rectangularity: query.rectangularity,
+                room_count: None,
+                compactness: query.compactness,
+                convexity: query.convexity,
+            },
+            candidate: StructureFeatures {
+                area: candidate.area,
+                bounding_box_aspect: candidate.bounding_box_aspect,
+                rectangularity: candidate.rectangularity,
+                room_count: None,
+                compactness: candidate.compactness,
+                convexity: candidate.convexity,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageResponse<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> PageResponse<T> {
+    pub fn new(items: Vec<T>, next_cursor: Option<String>) -> Self {
+        Self { items, next_cursor }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProjectRenderingsResponse {
@@ -306,3 +446,43 @@ impl RoomItemsResponse {
         })
     }
 }
+
+/// Outcome of rebuilding the structures of one or more projects.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureIndexResponse {
+    /// Projects that had at least one floorplan.
+    pub indexed_projects: u64,
+    pub floors: WriteSummary,
+    pub rooms: WriteSummary,
+}
+
+impl AddAssign for StructureIndexResponse {
+    fn add_assign(&mut self, other: Self) {
+        self.indexed_projects += other.indexed_projects;
+        self.floors += other.floors;
+        self.rooms += other.rooms;
+    }
+}
+
+/// Data issues found in the floorplan documents of one project.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FloorplanValidationResponse {
+    pub project_id: String,
+    /// Number of floorplan documents checked.
+    pub floorplans: usize,
+    pub valid: bool,
+    pub issues: Vec<FloorplanIssue>,
+}
+
+impl FloorplanValidationResponse {
+    pub fn new(project_id: String, floorplans: usize, issues: Vec<FloorplanIssue>) -> Self {
+        Self {
+            project_id,
+            floorplans,
+            valid: issues.is_empty(),
+            issues,
+        }
+    }
+}
@@ -1,16 +1,22 @@
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post},
 };
 
 use crate::{
     handlers::project_handler::{
-        create_project_structure, create_recent_project_structures, get_project_by_id,
-        get_project_renderings, get_room_items, get_similar_floors, get_similar_rooms,
+        create_project_structure, create_recent_project_structures, delete_project_structure,
+        get_project_by_id, get_project_renderings, get_room_items, get_similar_floors,
+        get_similar_rooms, search_similar_floors, search_similar_rooms,
+        validate_project_floorplans,
     },
     state::AppState,
 };
 
+/// Uploaded floorplan documents routinely exceed axum's 2 MB default body limit.
+const FLOORPLAN_BODY_LIMIT: usize = 32 * 1024 * 1024;
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/projects/{project_id}", get(get_project_by_id))
@@ -19,6 +25,10 @@ pub fn router() -> Router<AppState> {
             get(get_project_renderings),
         )
         .route("/projects/{room_key}/room-items", get(get_room_items))
+        .route(
+            "/projects/{project_id}/floorplans/validate",
+            get(validate_project_floorplans),
+        )
         .route(
             "/projects/{floor_id}/similar-floor",
             get(get_similar_floors),
@@ -26,10 +36,15 @@ pub fn router() -> Router<AppState> {
         .route("/projects/{room_id}/similar-room", get(get_similar_rooms))
         .route(
             "/projects/{project_id}/structure",
-            post(create_project_structure),
+            post(create_project_structure).delete(delete_project_structure),
         )
         .route(
             "/projects/structures",
             post(create_recent_project_structures),
         )
+        .route(
+            "/floors/similar",
+            post(search_similar_floors).layer(DefaultBodyLimit::max(FLOORPLAN_BODY_LIMIT)),
+        )
+        .route("/rooms/similar", post(search_similar_rooms))
 }
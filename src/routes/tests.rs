@@ -0,0 +1,724 @@
+use std::{path::Path, sync::Arc};
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{Method, Request, StatusCode},
+};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::{
+    models::job::{self, JobKind},
+    repositories::{
+        dependency_probe::DependencyProbe,
+        fixture_loader::load_fixtures,
+        floorplan_fetcher::{FetchOptions, FloorplanFetcher},
+        object_store::{HttpObjectStore, LocalObjectStore, ObjectStore},
+        structure_index::StructureIndex,
+    },
+    routes::app_router,
+    scoring::ScoringProfiles,
+    state::AppState,
+    workers::structure_jobs,
+};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+const CDN: &str = "https://cdn.example.com";
+
+/// State over the fixture projects, with in-memory repositories and no databases.
+fn fixture_state() -> AppState {
+    let root = Path::new(FIXTURES);
+    let fixtures = load_fixtures(root).expect("fixtures load");
+    let object_store: Arc<dyn ObjectStore> = Arc::new(LocalObjectStore::new(root.to_path_buf()));
+    let index = Arc::new(StructureIndex::open(None).expect("empty index"));
+
+    AppState {
+        project_repository: Some(Arc::new(fixtures.projects)),
+        image_repository: Some(Arc::new(fixtures.images)),
+        floor_structure_repository: index.clone(),
+        room_structure_repository: index,
+        index_watermark_repository: None,
+        change_stream_token_repository: None,
+        job_repository: None,
+        structure_index_error_repository: None,
+        in_memory_jobs: Arc::default(),
+        in_memory_watermarks: Arc::default(),
+        image_store: object_store.clone(),
+        cdn_base_url: CDN.to_string(),
+        floorplan_fetcher: FloorplanFetcher::new(object_store.clone(), FetchOptions::default()),
+        dependency_probe: DependencyProbe::new(object_store),
+        scoring_profiles: Arc::default(),
+        index_concurrency: 1,
+    }
+}
+
+/// The full router over [`fixture_state`].
+fn fixture_router() -> Router {
+    app_router().with_state(fixture_state())
+}
+
+async fn send(
+    router: &Router,
+    method: Method,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .expect("valid request");
+
+    let response = router.clone().oneshot(request).await.expect("infallible");
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("readable body");
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).expect("JSON body")
+    };
+
+    (status, body)
+}
+
+async fn index_fixtures(router: &Router) {
+    for project_id in ["project-a", "project-b", "project-c"] {
+        let uri = format!("/projects/{project_id}/structure");
+        let (status, body) = send(router, Method::POST, &uri, None).await;
+        assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+    }
+}
+
+fn ids(page: &Value, field: &str) -> Vec<String> {
+    page["items"]
+        .as_array()
+        .expect("page items")
+        .iter()
+        .map(|item| item[field].as_str().expect("string field").to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn creates_and_replaces_project_structure() {
+    let router = fixture_router();
+
+    let (status, body) = send(&router, Method::POST, "/projects/project-a/structure", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["indexedProjects"], 1);
+    assert_eq!(
+        body["floors"],
+        json!({ "inserted": 1, "updated": 0, "removed": 0 })
+    );
+    assert_eq!(
+        body["rooms"],
+        json!({ "inserted": 2, "updated": 0, "removed": 0 })
+    );
+
+    let (status, body) = send(&router, Method::POST, "/projects/project-a/structure", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body["floors"],
+        json!({ "inserted": 0, "updated": 1, "removed": 0 })
+    );
+    assert_eq!(
+        body["rooms"],
+        json!({ "inserted": 0, "updated": 2, "removed": 0 })
+    );
+}
+
+#[tokio::test]
+async fn deletes_the_floors_and_rooms_of_a_project() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let (status, _) = send(
+        &router,
+        Method::DELETE,
+        "/projects/project-b/structure",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_floor-a/similar-floor?areaFrom=0&areaTo=100",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(ids(&body, "projectId"), Vec::<String>::new());
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-b_project-b-living/similar-room?areaFrom=0&areaTo=100",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+}
+
+#[tokio::test]
+async fn ranks_floors_with_the_same_room_count() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_floor-a/similar-floor?areaFrom=0&areaTo=100&explain=true",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(ids(&body, "projectId"), ["project-b"]);
+    // Default weights: 0.25 × area 0.05 + 0.25 × aspect 0.119; the layouts match.
+    let explanation = &body["items"][0]["explanation"];
+    assert_eq!(explanation["distances"]["topology"], 0.0);
+    let score = explanation["score"].as_f64().expect("score");
+    assert!((score - 0.042_261_9).abs() < 1e-6, "score {score}");
+    assert_eq!(
+        body["items"][0]["imageUrls"],
+        json!(["https://cdn.example.com/images/image-b/1280x720/image-b.png"])
+    );
+}
+
+#[tokio::test]
+async fn ranks_rooms_of_the_same_type_from_other_projects() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_project-a-living/similar-room?areaFrom=0&areaTo=100",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let rooms = ids(&body, "id");
+    assert_eq!(
+        rooms.first().map(String::as_str),
+        Some("project-b_project-b-living")
+    );
+    assert!(rooms.iter().all(|id| !id.starts_with("project-a_")));
+}
+
+#[tokio::test]
+async fn rejects_a_cursor_from_a_different_area_band() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let uri = "/projects/project-a_project-a-living/similar-room?limit=1&areaFrom=0&areaTo=100";
+    let (status, body) = send(&router, Method::GET, uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let cursor = body["nextCursor"]
+        .as_str()
+        .expect("next cursor")
+        .to_string();
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        &format!("{uri}&cursor={cursor}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["items"].as_array().map(Vec::len), Some(1));
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        &format!("/projects/project-a_project-a-living/similar-room?limit=1&areaFrom=0&areaTo=90&cursor={cursor}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "bad_request");
+}
+
+/// Profiles with a `lenient` profile whose bands admit every fixture floor and room.
+fn lenient_profiles() -> Arc<ScoringProfiles> {
+    let bands = json!({
+        "aspectTolerance": 0.99,
+        "rectangularityTolerance": 1.0,
+    });
+    let mut floor = bands.clone();
+    floor["roomCountTolerance"] = json!(10);
+    let path = std::env::temp_dir().join(format!("scoring-profiles-{}.json", Uuid::new_v4()));
+    std::fs::write(
+        &path,
+        json!({ "lenient": { "floor": floor, "room": bands } }).to_string(),
+    )
+    .expect("writable profiles file");
+    let profiles = ScoringProfiles::load(path.to_str(), None);
+    let _ = std::fs::remove_file(&path);
+    Arc::new(profiles.expect("profiles load"))
+}
+
+/// Follow `nextCursor` from the first page of `uri` to the last, collecting item ids.
+async fn walk_pages(router: &Router, uri: &str) -> Vec<String> {
+    let (status, mut body) = send(router, Method::GET, uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let mut seen = ids(&body, "id");
+    while let Some(cursor) = body["nextCursor"].as_str() {
+        let next = format!("{uri}&cursor={cursor}");
+        let (status, page) = send(router, Method::GET, &next, None).await;
+        assert_eq!(status, StatusCode::OK, "{page}");
+        seen.extend(ids(&page, "id"));
+        body = page;
+    }
+    seen
+}
+
+#[tokio::test]
+async fn floor_pages_continue_from_the_cursor() {
+    let mut state = fixture_state();
+    state.scoring_profiles = lenient_profiles();
+    let router = app_router().with_state(state);
+    index_fixtures(&router).await;
+
+    let uri = "/projects/project-a_floor-a/similar-floor?profile=lenient&areaFrom=0&areaTo=100";
+    let (status, body) = send(&router, Method::GET, uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let all = ids(&body, "id");
+    assert_eq!(all, ["project-b_floor-b", "project-c_floor-c"]);
+
+    let paged = walk_pages(&router, &format!("{uri}&limit=1")).await;
+    assert_eq!(paged, all, "pages repeat or skip floors");
+}
+
+#[tokio::test]
+async fn room_pages_continue_from_the_cursor() {
+    let mut state = fixture_state();
+    state.scoring_profiles = lenient_profiles();
+    let router = app_router().with_state(state);
+    index_fixtures(&router).await;
+
+    let uri =
+        "/projects/project-a_project-a-living/similar-room?profile=lenient&areaFrom=0&areaTo=100";
+    let (status, body) = send(&router, Method::GET, uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let all = ids(&body, "id");
+    assert_eq!(
+        all,
+        ["project-b_project-b-living", "project-c_project-c-studio"]
+    );
+
+    let paged = walk_pages(&router, &format!("{uri}&limit=1")).await;
+    assert_eq!(paged, all, "pages repeat or skip rooms");
+}
+
+#[tokio::test]
+async fn unknown_scoring_profile_is_a_bad_request() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_floor-a/similar-floor?profile=lenient",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["message"], "scoring profile lenient not found");
+}
+
+fn fixture_floorplan(project_id: &str) -> Value {
+    let path = Path::new(FIXTURES)
+        .join("projects")
+        .join(project_id)
+        .join("floorplans.json");
+    let floorplans: Value =
+        serde_json::from_slice(&std::fs::read(path).expect("readable fixture")).expect("JSON");
+    floorplans[0].clone()
+}
+
+#[tokio::test]
+async fn searches_floors_by_floorplan() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let floorplan = fixture_floorplan("project-b");
+    let (status, body) = send(
+        &router,
+        Method::POST,
+        "/floors/similar?areaFrom=0&areaTo=100",
+        Some(floorplan),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let projects = ids(&body, "projectId");
+    assert_eq!(projects.first().map(String::as_str), Some("project-b"));
+}
+
+#[tokio::test]
+async fn measures_a_floorplan_sent_without_an_area() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let mut floorplan = fixture_floorplan("project-b");
+    let area = floorplan["area"].as_f64().expect("fixture area");
+    floorplan.as_object_mut().expect("object").remove("area");
+
+    // Without a band, the default tolerance is applied around the measured area.
+    let (status, body) = send(&router, Method::POST, "/floors/similar", Some(floorplan)).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let items = body["items"].as_array().expect("page items");
+    assert_eq!(items[0]["projectId"], "project-b");
+    assert_eq!(items[0]["area"].as_f64(), Some(area));
+}
+
+#[tokio::test]
+async fn floorplan_without_an_area_or_rooms_is_unprocessable() {
+    let router = fixture_router();
+
+    let mut floorplan = fixture_floorplan("project-b");
+    let object = floorplan.as_object_mut().expect("object");
+    object.remove("area");
+    object.insert("rooms".to_string(), json!([]));
+
+    let (status, body) = send(&router, Method::POST, "/floors/similar", Some(floorplan)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert_eq!(body["code"], "unprocessable");
+}
+
+#[tokio::test]
+async fn searches_rooms_by_outline() {
+    let router = fixture_router();
+    index_fixtures(&router).await;
+
+    let outline = json!({
+        "outline": [
+            { "x": 0.0, "z": 0.0 },
+            { "x": 4000.0, "z": 0.0 },
+            { "x": 4000.0, "z": 2500.0 },
+            { "x": 0.0, "z": 2500.0 }
+        ],
+        "type": 1
+    });
+    let (status, body) = send(&router, Method::POST, "/rooms/similar", Some(outline)).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        ids(&body, "id").first().map(String::as_str),
+        Some("project-c_project-c-studio")
+    );
+}
+
+#[tokio::test]
+async fn lists_the_items_of_a_room() {
+    let router = fixture_router();
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_project-a-living/room-items",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["floorplanId"], "floor-a-doc");
+    assert_eq!(body["roomLabel"], "Living room");
+    let products: Vec<_> = body["items"]
+        .as_array()
+        .expect("items")
+        .iter()
+        .map(|item| item["productId"].as_str().expect("product id"))
+        .collect();
+    assert_eq!(products, ["product-sofa", "product-table"]);
+}
+
+#[tokio::test]
+async fn unknown_room_is_not_found() {
+    let router = fixture_router();
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_missing/room-items",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+}
+
+#[tokio::test]
+async fn does_not_persist_the_fallback_cover_image_without_a_listing() {
+    let mut state = fixture_state();
+    state.image_store = Arc::new(HttpObjectStore::new(
+        reqwest::Client::new(),
+        CDN.to_string(),
+    ));
+    let projects = state.project_repository.clone().expect("fixture projects");
+    let router = app_router().with_state(state);
+
+    let (status, body) = send(&router, Method::GET, "/projects/project-c", None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["defaultCoverImage"], body["coverImage"]);
+    let project = projects
+        .get_by_id("project-c")
+        .await
+        .expect("fixture project");
+    assert_eq!(project.default_cover_image, None);
+}
+
+#[tokio::test]
+async fn validates_clean_floorplans() {
+    let router = fixture_router();
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a/floorplans/validate",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["floorplans"], 1);
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["issues"], json!([]));
+}
+
+#[tokio::test]
+async fn reports_floorplan_issues() {
+    let router = fixture_router();
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-d/floorplans/validate",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["valid"], false);
+    let issues: Vec<_> = body["issues"]
+        .as_array()
+        .expect("issues")
+        .iter()
+        .map(|issue| {
+            (
+                issue["kind"].as_str().expect("kind"),
+                issue["elementId"].as_str().expect("element id"),
+            )
+        })
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            ("incompleteWall", "floor-d-w1"),
+            ("unknownCorner", "project-d-living"),
+            ("selfIntersectingRoom", "project-d-bedroom"),
+            ("missingArea", "project-d-bedroom"),
+            ("emptyInnerPoints", "project-d-bedroom"),
+            ("unassignedItem", "item-d-lamp"),
+            ("duplicateArchiId", "project-d-bedroom"),
+        ]
+    );
+    assert!(
+        body["issues"]
+            .as_array()
+            .expect("issues")
+            .iter()
+            .all(|issue| issue["floorplanId"] == "floor-d-doc")
+    );
+}
+
+#[tokio::test]
+async fn indexing_an_unknown_project_is_not_found() {
+    let router = fixture_router();
+
+    let (status, body) = send(&router, Method::POST, "/projects/missing/structure", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+    assert_eq!(body["code"], "not_found");
+}
+
+#[tokio::test]
+async fn malformed_room_key_is_a_bad_request() {
+    let router = fixture_router();
+
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/no-separator/room-items",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "bad_request");
+    assert_eq!(body["message"], "invalid room identifier: no-separator");
+}
+
+#[tokio::test]
+async fn reports_liveness() {
+    let router = fixture_router();
+
+    let (status, body) = send(&router, Method::GET, "/healthz", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, json!({ "status": "ok" }));
+}
+
+#[tokio::test]
+async fn is_ready_without_optional_dependencies() {
+    let router = fixture_router();
+
+    let (status, body) = send(&router, Method::GET, "/readyz", None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["status"], "ready");
+    let statuses: Vec<_> = body["dependencies"]
+        .as_array()
+        .expect("dependencies")
+        .iter()
+        .map(|dependency| (dependency["name"].clone(), dependency["status"].clone()))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            (json!("mongo"), json!("notConfigured")),
+            (json!("postgres"), json!("notConfigured")),
+            (json!("objectStore"), json!("up")),
+            (json!("cdn"), json!("notConfigured")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn is_unavailable_when_a_dependency_is_down() {
+    let mut state = fixture_state();
+    let missing = Path::new(FIXTURES).join("missing");
+    state.dependency_probe = DependencyProbe::new(Arc::new(LocalObjectStore::new(missing)));
+    let router = app_router().with_state(state);
+
+    let (status, body) = send(&router, Method::GET, "/readyz", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{body}");
+    assert_eq!(body["status"], "unavailable");
+    let object_store = &body["dependencies"][2];
+    assert_eq!(object_store["name"], "objectStore");
+    assert_eq!(object_store["status"], "down");
+    assert!(object_store["error"].is_string());
+}
+
+#[tokio::test]
+async fn reports_failures_recorded_without_a_reason() {
+    let state = fixture_state();
+    let mut job = job::Model::new(JobKind::RecentIndex);
+    job.failures = json!([{ "projectId": "project-x", "error": "CDN unavailable" }]);
+    state.in_memory_jobs.save(&job).expect("job saved");
+    let router = app_router().with_state(state);
+
+    let (status, body) = send(&router, Method::GET, &format!("/jobs/{}", job.id), None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body["failures"],
+        json!([{ "projectId": "project-x", "reason": "internal", "error": "CDN unavailable" }])
+    );
+    assert_eq!(body["failureCounts"], json!({ "internal": 1 }));
+}
+
+#[tokio::test]
+async fn malformed_job_id_is_a_bad_request() {
+    let router = fixture_router();
+
+    let (status, body) = send(&router, Method::GET, "/jobs/not-a-uuid", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], "bad_request");
+    assert_eq!(body["message"], "invalid job identifier: not-a-uuid");
+}
+
+#[tokio::test]
+async fn coalesces_incremental_jobs_while_one_is_active() {
+    let state = fixture_state();
+    let mut finished = job::Model::new(JobKind::IncrementalIndex);
+    finished.finish(None);
+    state.in_memory_jobs.save(&finished).expect("job saved");
+    let mut active = job::Model::new(JobKind::IncrementalIndex);
+    active.start();
+    state.in_memory_jobs.save(&active).expect("job saved");
+
+    let job = structure_jobs::enqueue(&state, JobKind::IncrementalIndex)
+        .await
+        .expect("job enqueued");
+    assert_eq!(job.id, active.id);
+
+    active.finish(None);
+    state.in_memory_jobs.save(&active).expect("job saved");
+    let job = structure_jobs::enqueue(&state, JobKind::IncrementalIndex)
+        .await
+        .expect("job enqueued");
+    assert_ne!(job.id, active.id);
+    assert_ne!(job.id, finished.id);
+}
+
+#[tokio::test]
+async fn refreshing_during_a_full_reindex_is_a_conflict() {
+    let state = fixture_state();
+    let router = app_router().with_state(state.clone());
+    let mut full = job::Model::new(JobKind::FullReindex);
+    full.start();
+    state.in_memory_jobs.save(&full).expect("job saved");
+
+    let (status, body) = send(&router, Method::POST, "/projects/structures", None).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(
+        body,
+        json!({
+            "code": "conflict",
+            "message": "a full reindex is already queued or running",
+            "details": { "jobId": full.id },
+        })
+    );
+
+    full.finish(None);
+    state.in_memory_jobs.save(&full).expect("job saved");
+    let (status, body) = send(&router, Method::POST, "/projects/structures", None).await;
+    assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+}
+
+#[tokio::test]
+async fn full_reindex_covers_every_changed_project() {
+    let state = fixture_state();
+    let router = app_router().with_state(state.clone());
+
+    let job = structure_jobs::enqueue(&state, JobKind::FullReindex)
+        .await
+        .expect("job enqueued");
+    let again = structure_jobs::enqueue(&state, JobKind::FullReindex)
+        .await
+        .expect("job enqueued");
+    let job_id = job.id;
+    let job = loop {
+        let job = state
+            .in_memory_jobs
+            .find_by_id(job_id)
+            .expect("readable jobs")
+            .expect("job recorded");
+        if job.is_finished() {
+            break job;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    };
+
+    assert_eq!(again.id, job_id);
+    assert_eq!(job.error, None);
+    assert_eq!((job.total, job.processed), (4, 4));
+    let (status, body) = send(
+        &router,
+        Method::GET,
+        "/projects/project-a_floor-a/similar-floor?areaFrom=0&areaTo=100",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(ids(&body, "projectId"), ["project-b"]);
+}
@@ -0,0 +1,4 @@
+pub mod embedding;
+pub mod profile;
+
+pub use profile::{FloorScoring, RoomScoring, ScoringProfile, ScoringProfiles};
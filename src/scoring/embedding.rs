@@ -0,0 +1,77 @@
+use crate::geometry::fourier::DESCRIPTOR_LEN;
+
+pub const FLOOR_EMBEDDING_DIM: usize = 4;
+pub const ROOM_EMBEDDING_DIM: usize = 5 + DESCRIPTOR_LEN;
+
+/// Floor area in square metres at which the log-scaled area component reaches `1.0`.
+const FLOOR_AREA_RANGE: f64 = 2_000.0;
+/// Room area in square metres at which the log-scaled area component reaches `1.0`.
+const ROOM_AREA_RANGE: f64 = 200.0;
+/// Room count at which the log-scaled room count component reaches `1.0`.
+const ROOM_COUNT_RANGE: f64 = 50.0;
+
+/// Embedding of the floor features compared by the floor scorer.
+///
+/// Area (in square metres) and room count are log-scaled into `[0, 1]` and the aspect is
+/// inverted, so every component has the same range for L2 nearest-neighbour search.
+pub fn floor_embedding(area: f64, aspect: f64, rectangularity: f64, room_count: i32) -> Vec<f64> {
+    let mut embedding = Vec::with_capacity(FLOOR_EMBEDDING_DIM);
+    embedding.push(log_unit(area, FLOOR_AREA_RANGE));
+    embedding.push(inverse_aspect(aspect));
+    embedding.push(rectangularity);
+    embedding.push(log_unit(f64::from(room_count), ROOM_COUNT_RANGE));
+    sanitize(embedding)
+}
+
+/// Embedding of the room features compared by the room scorer, including the outline descriptor.
+///
+/// `area` is in square millimetres like `room.area` and is log-scaled into `[0, 1]`.
+pub fn room_embedding(
+    area: f64,
+    aspect: f64,
+    rectangularity: f64,
+    compactness: Option<f64>,
+    convexity: Option<f64>,
+    shape_descriptor: Option<&[f64]>,
+) -> Vec<f64> {
+    let mut embedding = Vec::with_capacity(ROOM_EMBEDDING_DIM);
+    embedding.push(log_unit(area / 1_000_000.0, ROOM_AREA_RANGE));
+    embedding.push(inverse_aspect(aspect));
+    embedding.push(rectangularity);
+    embedding.push(compactness.unwrap_or_default());
+    embedding.push(convexity.unwrap_or_default());
+    let descriptor = shape_descriptor.unwrap_or_default();
+    embedding.extend((0..DESCRIPTOR_LEN).map(|i| descriptor.get(i).copied().unwrap_or_default()));
+    sanitize(embedding)
+}
+
+/// Render an embedding as a pgvector literal, e.g. `[0.1,0.2]`.
+pub fn to_vector_literal(embedding: &[f64]) -> String {
+    let values: Vec<String> = embedding.iter().map(|value| value.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
+
+/// `ln(1 + value)` relative to `ln(1 + range)`, clamped to `[0, 1]`.
+fn log_unit(value: f64, range: f64) -> f64 {
+    (value.max(0.0).ln_1p() / range.ln_1p()).min(1.0)
+}
+
+fn inverse_aspect(aspect: f64) -> f64 {
+    if aspect.is_finite() && aspect > 0.0 {
+        (1.0 / aspect).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+fn sanitize(mut embedding: Vec<f64>) -> Vec<f64> {
+    for value in &mut embedding {
+        if !value.is_finite() {
+            *value = 0.0;
+        }
+    }
+    embedding
+}
+
+#[cfg(test)]
+mod tests;
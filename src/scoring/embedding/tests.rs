@@ -0,0 +1,40 @@
+use super::{
+    FLOOR_EMBEDDING_DIM, ROOM_EMBEDDING_DIM, floor_embedding, room_embedding, to_vector_literal,
+};
+
+#[test]
+fn floor_components_share_the_unit_range() {
+    let embedding = floor_embedding(120.0, 1.5, 0.9, 8);
+
+    assert_eq!(embedding.len(), FLOOR_EMBEDDING_DIM);
+    assert!(embedding.iter().all(|value| (0.0..=1.0).contains(value)));
+    assert_eq!(floor_embedding(1e9, 1.0, 1.0, 1_000)[0], 1.0);
+    assert_eq!(floor_embedding(1e9, 1.0, 1.0, 1_000)[3], 1.0);
+}
+
+#[test]
+fn room_area_does_not_dominate_the_distance() {
+    let small = room_embedding(9_000_000.0, 1.0, 1.0, Some(0.78), Some(1.0), None);
+    let large = room_embedding(36_000_000.0, 1.0, 1.0, Some(0.78), Some(1.0), None);
+    let narrow = room_embedding(9_000_000.0, 4.0, 1.0, Some(0.5), Some(1.0), None);
+
+    assert_eq!(small.len(), ROOM_EMBEDDING_DIM);
+    assert!(small[0] > 0.0 && small[0] < 1.0);
+    // Quadrupling the area moves the embedding less than a much narrower outline does.
+    let distance = |a: &[f64], b: &[f64]| -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<f64>()
+            .sqrt()
+    };
+    assert!(distance(&small, &large) < distance(&small, &narrow));
+}
+
+#[test]
+fn non_finite_components_are_zeroed() {
+    let embedding = room_embedding(f64::NAN, 0.0, f64::INFINITY, None, None, None);
+
+    assert!(embedding.iter().all(|value| *value == 0.0));
+    assert_eq!(to_vector_literal(&embedding[..3]), "[0,0,0]".to_string());
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, anyhow, ensure};
+use serde::Deserialize;
+
+pub const DEFAULT_PROFILE_NAME: &str = "default";
+
+/// Weights and tolerance bands used to rank similar floors.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FloorScoring {
+    pub area_weight: f64,
+    pub aspect_weight: f64,
+    pub rectangularity_weight: f64,
+    pub room_count_weight: f64,
+    /// Weight of the room adjacency graph distance.
+    ///
+    /// Floors indexed before graph signatures were stored take the maximum distance until
+    /// they are reindexed.
+    pub topology_weight: f64,
+    /// Relative area band applied when the request does not pass `areaFrom`/`areaTo`.
+    pub area_tolerance: f64,
+    /// Lower bound for the area distance normalizer.
+    pub min_area_normalizer: f64,
+    /// Relative band around the query bounding box aspect.
+    pub aspect_tolerance: f64,
+    /// Absolute band around the query rectangularity.
+    pub rectangularity_tolerance: f64,
+    /// Absolute band around the query room count.
+    pub room_count_tolerance: i32,
+}
+
+impl Default for FloorScoring {
+    fn default() -> Self {
+        Self {
+            area_weight: 0.25,
+            aspect_weight: 0.25,
+            rectangularity_weight: 0.15,
+            room_count_weight: 0.15,
+            topology_weight: 0.2,
+            area_tolerance: 0.15,
+            min_area_normalizer: 30.0,
+            aspect_tolerance: 0.15,
+            rectangularity_tolerance: 0.1,
+            room_count_tolerance: 3,
+        }
+    }
+}
+
+/// Weights and tolerance bands used to rank similar rooms.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RoomScoring {
+    pub area_weight: f64,
+    pub aspect_weight: f64,
+    pub rectangularity_weight: f64,
+    /// Weight of the isoperimetric compactness of the room outline.
+    ///
+    /// Rooms indexed before outlines were stored take the maximum distance until they are
+    /// reindexed.
+    pub compactness_weight: f64,
+    /// Weight of the outline area over its convex hull area; missing values are treated like
+    /// `compactness_weight`.
+    pub convexity_weight: f64,
+    /// Weight of the Fourier outline descriptor distance.
+    ///
+    /// Off by default: rooms indexed before descriptors were stored take the maximum distance
+    /// until they are reindexed. Profiles enabling it should rebalance the other weights.
+    pub outline_weight: f64,
+    /// Relative area band applied when the request does not pass `areaFrom`/`areaTo`.
+    pub area_tolerance: f64,
+    /// Lower bound for the area distance normalizer.
+    pub min_area_normalizer: f64,
+    /// Relative band around the query bounding box aspect.
+    pub aspect_tolerance: f64,
+    /// Absolute band around the query rectangularity.
+    pub rectangularity_tolerance: f64,
+}
+
+impl Default for RoomScoring {
+    fn default() -> Self {
+        Self {
+            area_weight: 0.25,
+            aspect_weight: 0.4,
+            rectangularity_weight: 0.15,
+            compactness_weight: 0.1,
+            convexity_weight: 0.1,
+            outline_weight: 0.0,
+            area_tolerance: 0.15,
+            min_area_normalizer: 5.0,
+            aspect_tolerance: 0.15,
+            rectangularity_tolerance: 0.1,
+        }
+    }
+}
+
+impl FloorScoring {
+    fn validate(&self) -> Result<()> {
+        check_weights(&[
+            ("areaWeight", self.area_weight),
+            ("aspectWeight", self.aspect_weight),
+            ("rectangularityWeight", self.rectangularity_weight),
+            ("roomCountWeight", self.room_count_weight),
+            ("topologyWeight", self.topology_weight),
+        ])?;
+        check_bands(
+            self.area_tolerance,
+            self.min_area_normalizer,
+            self.aspect_tolerance,
+            self.rectangularity_tolerance,
+        )?;
+        ensure!(
+            self.room_count_tolerance >= 0,
+            "roomCountTolerance must not be negative"
+        );
+        Ok(())
+    }
+}
+
+impl RoomScoring {
+    fn validate(&self) -> Result<()> {
+        check_weights(&[
+            ("areaWeight", self.area_weight),
+            ("aspectWeight", self.aspect_weight),
+            ("rectangularityWeight", self.rectangularity_weight),
+            ("compactnessWeight", self.compactness_weight),
+            ("convexityWeight", self.convexity_weight),
+            ("outlineWeight", self.outline_weight),
+        ])?;
+        check_bands(
+            self.area_tolerance,
+            self.min_area_normalizer,
+            self.aspect_tolerance,
+            self.rectangularity_tolerance,
+        )
+    }
+}
+
+fn check_weights(weights: &[(&str, f64)]) -> Result<()> {
+    for (name, weight) in weights {
+        ensure!(
+            weight.is_finite() && *weight >= 0.0,
+            "{name} must be a non-negative number, got {weight}"
+        );
+    }
+    Ok(())
+}
+
+/// Relative bands must stay below `1.0`, or their lower bound would turn negative.
+fn check_bands(
+    area_tolerance: f64,
+    min_area_normalizer: f64,
+    aspect_tolerance: f64,
+    rectangularity_tolerance: f64,
+) -> Result<()> {
+    for (name, tolerance) in [
+        ("areaTolerance", area_tolerance),
+        ("aspectTolerance", aspect_tolerance),
+    ] {
+        ensure!(
+            (0.0..1.0).contains(&tolerance),
+            "{name} must be in [0, 1), got {tolerance}"
+        );
+    }
+    ensure!(
+        rectangularity_tolerance.is_finite() && rectangularity_tolerance >= 0.0,
+        "rectangularityTolerance must not be negative, got {rectangularity_tolerance}"
+    );
+    ensure!(
+        min_area_normalizer.is_finite() && min_area_normalizer > 0.0,
+        "minAreaNormalizer must be positive, got {min_area_normalizer}"
+    );
+    Ok(())
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScoringProfile {
+    pub floor: FloorScoring,
+    pub room: RoomScoring,
+}
+
+/// Named scoring profiles, one of which is used when a request does not pick one.
+#[derive(Clone, Debug)]
+pub struct ScoringProfiles {
+    default_name: String,
+    profiles: HashMap<String, ScoringProfile>,
+}
+
+impl Default for ScoringProfiles {
+    fn default() -> Self {
+        let mut profiles = HashMap::new();
+        profiles.insert(DEFAULT_PROFILE_NAME.to_string(), ScoringProfile::default());
+        Self {
+            default_name: DEFAULT_PROFILE_NAME.to_string(),
+            profiles,
+        }
+    }
+}
+
+impl ScoringProfiles {
+    /// Load profiles from a JSON file mapping profile names to profiles.
+    ///
+    /// The built-in `default` profile is always available unless the file overrides it.
+    /// Negative weights and negative or inverted tolerance bands are rejected.
+    pub fn load(path: Option<&str>, default_name: Option<&str>) -> Result<Self> {
+        let mut scoring_profiles = Self::default();
+
+        if let Some(path) = path {
+            let payload = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read scoring profiles from {path}"))?;
+            let profiles: HashMap<String, ScoringProfile> = serde_json::from_str(&payload)
+                .with_context(|| format!("failed to parse scoring profiles from {path}"))?;
+            for (name, profile) in &profiles {
+                profile
+                    .floor
+                    .validate()
+                    .with_context(|| format!("scoring profile {name} has invalid floor scoring"))?;
+                profile
+                    .room
+                    .validate()
+                    .with_context(|| format!("scoring profile {name} has invalid room scoring"))?;
+            }
+            scoring_profiles.profiles.extend(profiles);
+        }
+
+        if let Some(name) = default_name {
+            if !scoring_profiles.profiles.contains_key(name) {
+                return Err(anyhow!("default scoring profile {name} is not defined"));
+            }
+            scoring_profiles.default_name = name.to_string();
+        }
+
+        Ok(scoring_profiles)
+    }
+
+    pub fn get(&self, name: Option<&str>) -> Option<&ScoringProfile> {
+        self.profiles.get(name.unwrap_or(&self.default_name))
+    }
+}
+
+#[cfg(test)]
+mod tests;
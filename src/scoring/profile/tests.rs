@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use super::{DEFAULT_PROFILE_NAME, ScoringProfile, ScoringProfiles};
+
+/// Profiles file in the temporary directory, removed when dropped.
+struct ProfilesFile(PathBuf);
+
+impl ProfilesFile {
+    fn new(profiles: Value) -> Self {
+        let path = std::env::temp_dir().join(format!("scoring-profiles-{}.json", Uuid::new_v4()));
+        std::fs::write(&path, profiles.to_string()).expect("writable profiles file");
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().expect("utf-8 path")
+    }
+}
+
+impl Drop for ProfilesFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn strict() -> Value {
+    json!({ "strict": { "room": { "areaTolerance": 0.05, "outlineWeight": 0.4 } } })
+}
+
+#[test]
+fn uses_the_built_in_default_without_a_file() {
+    let profiles = ScoringProfiles::load(None, None).expect("profiles load");
+
+    assert_eq!(profiles.get(None), Some(&ScoringProfile::default()));
+    assert_eq!(
+        profiles.get(Some(DEFAULT_PROFILE_NAME)),
+        Some(&ScoringProfile::default())
+    );
+    assert_eq!(profiles.get(Some("strict")), None);
+}
+
+#[test]
+fn a_request_can_pick_another_profile_than_the_default() {
+    let file = ProfilesFile::new(strict());
+    let profiles = ScoringProfiles::load(Some(file.path()), None).expect("profiles load");
+
+    assert_eq!(profiles.get(None), Some(&ScoringProfile::default()));
+    let strict = profiles.get(Some("strict")).expect("strict profile");
+    assert_eq!(strict.room.area_tolerance, 0.05);
+    assert_eq!(strict.room.outline_weight, 0.4);
+    // Fields a profile leaves out keep their defaults.
+    assert_eq!(strict.floor, ScoringProfile::default().floor);
+}
+
+#[test]
+fn the_default_can_be_switched_to_a_loaded_profile() {
+    let file = ProfilesFile::new(strict());
+    let profiles = ScoringProfiles::load(Some(file.path()), Some("strict")).expect("profiles load");
+
+    assert_eq!(profiles.get(None), profiles.get(Some("strict")));
+    assert_eq!(
+        profiles.get(Some(DEFAULT_PROFILE_NAME)),
+        Some(&ScoringProfile::default())
+    );
+    assert!(ScoringProfiles::load(Some(file.path()), Some("lenient")).is_err());
+}
+
+#[test]
+fn rejects_negative_weights_and_inverted_bands() {
+    for (profile, field) in [
+        (json!({ "floor": { "areaWeight": -0.1 } }), "areaWeight"),
+        (
+            json!({ "room": { "outlineWeight": -1.0 } }),
+            "outlineWeight",
+        ),
+        (
+            json!({ "room": { "areaTolerance": -0.2 } }),
+            "areaTolerance",
+        ),
+        (
+            json!({ "floor": { "aspectTolerance": 1.5 } }),
+            "aspectTolerance",
+        ),
+        (
+            json!({ "room": { "rectangularityTolerance": -0.1 } }),
+            "rectangularityTolerance",
+        ),
+        (
+            json!({ "floor": { "roomCountTolerance": -1 } }),
+            "roomCountTolerance",
+        ),
+        (
+            json!({ "room": { "minAreaNormalizer": 0.0 } }),
+            "minAreaNormalizer",
+        ),
+    ] {
+        let file = ProfilesFile::new(json!({ "broken": profile }));
+        let err = ScoringProfiles::load(Some(file.path()), None).expect_err(field);
+        assert!(format!("{err:#}").contains(field), "{err:#}");
+    }
+}
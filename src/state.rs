@@ -1,49 +1,88 @@
-use anyhow::anyhow;
-use aws_sdk_s3::Client as S3Client;
-use reqwest::Client as HttpClient;
+use std::sync::Arc;
 
 use crate::{
     error::ApiError,
     repositories::{
-        floor_structure_repository::FloorStructureRepository, image_repository::ImageRepository,
-        project_repository::ProjectRepository, room_structure_repository::RoomStructureRepository,
+        change_stream_token_repository::ChangeStreamTokenRepository,
+        dependency_probe::DependencyProbe,
+        floorplan_fetcher::FloorplanFetcher,
+        image_repository::ImageStore,
+        index_watermark_repository::{
+            InMemoryIndexWatermarkRepository, IndexWatermarkRepository, WatermarkStore,
+        },
+        job_repository::{InMemoryJobRepository, JobRepository, JobStore},
+        object_store::ObjectStore,
+        project_repository::ProjectStore,
+        structure_index_error_repository::StructureIndexErrorRepository,
+        structure_store::{FloorStructureStore, RoomStructureStore},
     },
+    scoring::{ScoringProfile, ScoringProfiles},
 };
 
 #[derive(Clone)]
 pub struct AppState {
-    pub project_repository: Option<ProjectRepository>,
-    pub image_repository: Option<ImageRepository>,
-    pub floor_structure_repository: Option<FloorStructureRepository>,
-    pub room_structure_repository: Option<RoomStructureRepository>,
-    pub s3_client: Option<S3Client>,
-    pub s3_bucket: Option<String>,
+    pub project_repository: Option<Arc<dyn ProjectStore>>,
+    pub image_repository: Option<Arc<dyn ImageStore>>,
+    /// Postgres when configured, the in-process structure index otherwise.
+    pub floor_structure_repository: Arc<dyn FloorStructureStore>,
+    pub room_structure_repository: Arc<dyn RoomStructureStore>,
+    pub index_watermark_repository: Option<IndexWatermarkRepository>,
+    pub change_stream_token_repository: Option<ChangeStreamTokenRepository>,
+    pub job_repository: Option<JobRepository>,
+    pub structure_index_error_repository: Option<StructureIndexErrorRepository>,
+    pub in_memory_jobs: Arc<InMemoryJobRepository>,
+    pub in_memory_watermarks: Arc<InMemoryIndexWatermarkRepository>,
+    /// Lists cover images; S3 when a bucket is configured, `object_store` otherwise.
+    pub image_store: Arc<dyn ObjectStore>,
     pub cdn_base_url: String,
-    pub http_client: HttpClient,
+    pub floorplan_fetcher: FloorplanFetcher,
+    pub dependency_probe: DependencyProbe,
+    pub scoring_profiles: Arc<ScoringProfiles>,
+    /// Projects an indexing job rebuilds at the same time.
+    pub index_concurrency: usize,
 }
 
 impl AppState {
-    pub fn project_repository(&self) -> Result<&ProjectRepository, ApiError> {
+    pub fn project_repository(&self) -> Result<&dyn ProjectStore, ApiError> {
         self.project_repository
-            .as_ref()
-            .ok_or_else(|| ApiError::internal(anyhow!("Mongo connection is not configured")))
+            .as_deref()
+            .ok_or_else(|| ApiError::service_unavailable("Mongo connection is not configured"))
     }
 
-    pub fn image_repository(&self) -> Result<&ImageRepository, ApiError> {
+    pub fn image_repository(&self) -> Result<&dyn ImageStore, ApiError> {
         self.image_repository
-            .as_ref()
-            .ok_or_else(|| ApiError::internal(anyhow!("Mongo connection is not configured")))
+            .as_deref()
+            .ok_or_else(|| ApiError::service_unavailable("Mongo connection is not configured"))
     }
 
-    pub fn floor_structure_repository(&self) -> Result<&FloorStructureRepository, ApiError> {
-        self.floor_structure_repository
-            .as_ref()
-            .ok_or_else(|| ApiError::internal(anyhow!("Postgres connection is not configured")))
+    pub fn floor_structure_repository(&self) -> &dyn FloorStructureStore {
+        self.floor_structure_repository.as_ref()
     }
 
-    pub fn room_structure_repository(&self) -> Result<&RoomStructureRepository, ApiError> {
-        self.room_structure_repository
-            .as_ref()
-            .ok_or_else(|| ApiError::internal(anyhow!("Postgres connection is not configured")))
+    pub fn room_structure_repository(&self) -> &dyn RoomStructureStore {
+        self.room_structure_repository.as_ref()
+    }
+
+    pub fn job_repository(&self) -> JobStore<'_> {
+        match &self.job_repository {
+            Some(repository) => JobStore::Postgres(repository),
+            None => JobStore::InMemory(&self.in_memory_jobs),
+        }
+    }
+
+    pub fn index_watermarks(&self) -> WatermarkStore<'_> {
+        match &self.index_watermark_repository {
+            Some(repository) => WatermarkStore::Postgres(repository),
+            None => WatermarkStore::InMemory(&self.in_memory_watermarks),
+        }
+    }
+
+    pub fn scoring_profile(&self, name: Option<&str>) -> Result<&ScoringProfile, ApiError> {
+        self.scoring_profiles.get(name).ok_or_else(|| {
+            ApiError::bad_request(format!(
+                "scoring profile {} not found",
+                name.unwrap_or_default()
+            ))
+        })
     }
 }
@@ -1,2 +1,4 @@
+pub mod cursor;
+pub mod hash;
 pub mod image;
 pub mod serde;
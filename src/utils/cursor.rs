@@ -0,0 +1,92 @@
+use anyhow::{Result, anyhow};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+use crate::utils::hash::fnv1a;
+
+/// Keyset position in a similarity ranking ordered by `(score, id)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimilarityCursor {
+    pub score: f64,
+    pub id: String,
+    /// Results returned up to and including this position.
+    pub offset: u64,
+    /// [`SimilarityCursor::fingerprint`] of the ranking the cursor was issued for.
+    pub ranking: u64,
+}
+
+impl SimilarityCursor {
+    pub fn new(score: f64, id: impl Into<String>, offset: u64) -> Self {
+        Self {
+            score,
+            id: id.into(),
+            offset,
+            ranking: 0,
+        }
+    }
+
+    pub fn with_ranking(mut self, ranking: u64) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Stable FNV-1a hash of a ranking description (query, scoring profile and bands), so a
+    /// token is only accepted for the ranking it was issued for, on any instance.
+    pub fn fingerprint(ranking: &str) -> u64 {
+        fnv1a(ranking.as_bytes())
+    }
+
+    /// Encode the cursor as an opaque, URL-safe token.
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{:016x}:{}:{}:{}",
+            self.ranking, self.offset, self.score, self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(token: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|err| anyhow!("invalid cursor encoding: {err}"))?;
+        let raw = String::from_utf8(bytes).map_err(|err| anyhow!("invalid cursor: {err}"))?;
+        let mut parts = raw.splitn(4, ':');
+        let (Some(ranking), Some(offset), Some(score), Some(id)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!("invalid cursor: missing separator"));
+        };
+        let ranking = u64::from_str_radix(ranking, 16)
+            .map_err(|err| anyhow!("invalid cursor ranking: {err}"))?;
+        let offset = offset
+            .parse::<u64>()
+            .map_err(|err| anyhow!("invalid cursor offset: {err}"))?;
+        let score = score
+            .parse::<f64>()
+            .map_err(|err| anyhow!("invalid cursor score: {err}"))?;
+        if !score.is_finite() || id.is_empty() {
+            return Err(anyhow!("invalid cursor: {raw}"));
+        }
+
+        Ok(Self::new(score, id, offset).with_ranking(ranking))
+    }
+}
+
+/// Page size `k` capped to what is left of an approximate-neighbour pool of `candidates`.
+///
+/// The pool is recomputed on every request, so pages stop once the first `candidates` results
+/// have been returned rather than drawing on neighbours a shifted pool let in.
+pub fn remaining_candidates(
+    candidates: Option<u64>,
+    k: u64,
+    after: Option<&SimilarityCursor>,
+) -> u64 {
+    match candidates {
+        Some(candidates) => {
+            k.min(candidates.saturating_sub(after.map_or(0, |cursor| cursor.offset)))
+        }
+        None => k,
+    }
+}
+
+#[cfg(test)]
+mod tests;
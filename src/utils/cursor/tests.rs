@@ -0,0 +1,54 @@
+use super::{SimilarityCursor, remaining_candidates};
+
+#[test]
+fn round_trips_through_the_token() {
+    let cursor = SimilarityCursor::new(0.125, "project-a_room:with:colons", 20)
+        .with_ranking(SimilarityCursor::fingerprint("room|default|0|100"));
+
+    let token = cursor.encode();
+    assert!(
+        token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    );
+    assert_eq!(SimilarityCursor::decode(&token).expect("decodable"), cursor);
+}
+
+#[test]
+fn rejects_malformed_tokens() {
+    let encode = |raw: &str| base64::Engine::encode(&super::URL_SAFE_NO_PAD, raw);
+
+    for token in [
+        "not base64!".to_string(),
+        encode("0.5:room"),
+        encode("zz:1:0.5:room"),
+        encode("00000000000000ff:-1:0.5:room"),
+        encode("00000000000000ff:1:NaN:room"),
+        encode("00000000000000ff:1:0.5:"),
+    ] {
+        assert!(SimilarityCursor::decode(&token).is_err(), "{token}");
+    }
+}
+
+#[test]
+fn fingerprint_is_stable_and_tells_rankings_apart() {
+    assert_eq!(SimilarityCursor::fingerprint(""), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(
+        SimilarityCursor::fingerprint("room|default|0|100"),
+        SimilarityCursor::fingerprint("room|default|0|100")
+    );
+    assert_ne!(
+        SimilarityCursor::fingerprint("room|default|0|100"),
+        SimilarityCursor::fingerprint("room|default|0|90")
+    );
+}
+
+#[test]
+fn later_pages_stay_within_the_candidate_pool() {
+    let after = |offset| SimilarityCursor::new(0.5, "room", offset);
+
+    assert_eq!(remaining_candidates(None, 11, Some(&after(500))), 11);
+    assert_eq!(remaining_candidates(Some(50), 11, None), 11);
+    assert_eq!(remaining_candidates(Some(50), 11, Some(&after(40))), 10);
+    assert_eq!(remaining_candidates(Some(50), 11, Some(&after(60))), 0);
+}
@@ -0,0 +1,2 @@
+pub mod project_changes;
+pub mod structure_jobs;
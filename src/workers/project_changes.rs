@@ -0,0 +1,244 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use mongodb::{
+    change_stream::event::{OperationType, ResumeToken, UpdateDescription},
+    error::{Error as MongoError, ErrorKind as MongoErrorKind},
+};
+
+use crate::{
+    error::ApiError,
+    handlers::project_handler::{delete_project_structures, reindex_project},
+    models::job::JobKind,
+    repositories::project_repository::ProjectRepository,
+    state::AppState,
+    workers::structure_jobs::{self, record_failure},
+};
+
+/// Resume token row of the projects change stream.
+const RESUME_TOKEN_NAME: &str = "projects";
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+/// Times an event that fails transiently is replayed before its project is left to the
+/// incremental sweep.
+const MAX_EVENT_ATTEMPTS: u32 = 3;
+/// Fields the service writes itself; they play no part in the structures.
+const COVER_IMAGE_FIELDS: [&str; 3] = ["coverImage", "coverRenderImageId", "defaultCoverImage"];
+
+/// Keep floor and room structures in sync with the `projects` collection.
+///
+/// Requires Mongo to run as a replica set. Resume tokens are stored in Postgres when it is
+/// configured; otherwise the stream starts from the current time after every restart.
+///
+/// When the stored token is unreadable or has aged out of the oplog, the changes since are
+/// unknown, so every project is reindexed and the stream starts over from the current time.
+pub async fn run(state: AppState, projects: ProjectRepository) {
+    let mut resume_token = load_resume_token(&state).await;
+    let mut retries = EventRetries::default();
+
+    loop {
+        match watch(&state, &projects, &mut resume_token, &mut retries).await {
+            Ok(Stop::Closed) => tracing::warn!("project change stream closed"),
+            Ok(Stop::HistoryLost) => {
+                tracing::warn!("resume token is no longer in the oplog; reindexing every project");
+                if start_over(&state, &mut resume_token).await {
+                    continue;
+                }
+            }
+            Err(err) => tracing::error!(error = %err, "project change stream failed"),
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+/// Why [`watch`] returned without an error.
+enum Stop {
+    Closed,
+    /// The resume point is gone; resuming from the same token will keep failing.
+    HistoryLost,
+}
+
+/// Consecutive transient failures of the event the stream is replaying.
+#[derive(Default)]
+struct EventRetries {
+    project_id: Option<String>,
+    attempts: u32,
+}
+
+impl EventRetries {
+    /// Count a transient failure of `project_id`; `false` once it has used up its attempts.
+    fn retry(&mut self, project_id: &str) -> bool {
+        if self.project_id.as_deref() == Some(project_id) {
+            self.attempts += 1;
+        } else {
+            self.project_id = Some(project_id.to_string());
+            self.attempts = 1;
+        }
+        self.attempts < MAX_EVENT_ATTEMPTS
+    }
+}
+
+/// Load the stored resume token, retrying while Postgres is unavailable.
+async fn load_resume_token(state: &AppState) -> Option<ResumeToken> {
+    let tokens = state.change_stream_token_repository.as_ref()?;
+
+    loop {
+        match tokens.find(RESUME_TOKEN_NAME).await {
+            Ok(token) => return token,
+            Err(err @ ApiError::ServiceUnavailable { .. }) => {
+                tracing::error!(error = %err, "failed to load resume token");
+            }
+            Err(err) => {
+                tracing::error!(error = %err, "resume token is unreadable; reindexing every project");
+                let mut resume_token = None;
+                if start_over(state, &mut resume_token).await {
+                    return None;
+                }
+            }
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+/// Queue a full reindex to cover the changes the stream missed, then forget the resume token.
+///
+/// Returns `false` when the reindex could not be queued; the token is kept so the next
+/// attempt runs into the same condition and tries again.
+async fn start_over(state: &AppState, resume_token: &mut Option<ResumeToken>) -> bool {
+    match structure_jobs::enqueue(state, JobKind::FullReindex).await {
+        Ok(job) => tracing::info!(job_id = %job.id, "queued a full reindex"),
+        Err(err) => {
+            tracing::error!(error = %err, "failed to queue a full reindex");
+            return false;
+        }
+    }
+
+    // A stale row is overwritten by the first token saved from the new stream.
+    if let Some(tokens) = &state.change_stream_token_repository
+        && let Err(err) = tokens.delete(RESUME_TOKEN_NAME).await
+    {
+        tracing::error!(error = %err, "failed to clear resume token");
+    }
+    *resume_token = None;
+    true
+}
+
+/// Leave a project that keeps failing to the next incremental sweep, which retries it under
+/// its own attempt limit, and queue that sweep now.
+async fn hand_to_sweep(state: &AppState, project_id: &str) {
+    tracing::warn!(
+        project_id,
+        attempts = MAX_EVENT_ATTEMPTS,
+        "leaving a project that keeps failing to the incremental sweep"
+    );
+    let kind = structure_jobs::changed_projects_job(state);
+    match structure_jobs::enqueue(state, kind).await {
+        // A running full reindex rebuilds the project as well.
+        Ok(_) | Err(ApiError::Conflict { .. }) => {}
+        Err(err) => {
+            tracing::error!(project_id, error = %err, "failed to queue an incremental index")
+        }
+    }
+}
+
+/// Whether an update only set or removed cover image fields, e.g. through
+/// `persist_default_cover_image`.
+fn only_touches_cover_images(update: &UpdateDescription) -> bool {
+    let is_cover_image = |path: &str| {
+        let field = path.split('.').next().unwrap_or(path);
+        COVER_IMAGE_FIELDS.contains(&field)
+    };
+
+    update
+        .updated_fields
+        .keys()
+        .chain(&update.removed_fields)
+        .all(|path| is_cover_image(path))
+        && update
+            .truncated_arrays
+            .as_ref()
+            .is_none_or(|arrays| arrays.iter().all(|array| is_cover_image(&array.field)))
+}
+
+/// The server no longer has the resume point: `ChangeStreamHistoryLost`, or
+/// `ChangeStreamFatalError` from servers that predate it.
+fn history_lost(err: &MongoError) -> bool {
+    matches!(&*err.kind, MongoErrorKind::Command(command) if matches!(command.code, 280 | 286))
+}
+
+/// Apply change events until the stream ends. The resume token only advances past events
+/// that were applied or failed permanently, so a transient failure ends the stream and the
+/// next attempt replays the event from the last saved token. After [`MAX_EVENT_ATTEMPTS`]
+/// the project is handed to the incremental sweep and the stream moves on.
+///
+/// Updates that only touch cover images are skipped.
+async fn watch(
+    state: &AppState,
+    projects: &ProjectRepository,
+    resume_token: &mut Option<ResumeToken>,
+    retries: &mut EventRetries,
+) -> Result<Stop, ApiError> {
+    let mut stream = match projects.watch_changes(resume_token.clone()).await {
+        Ok(stream) => stream,
+        Err(err) if resume_token.is_some() && history_lost(&err) => return Ok(Stop::HistoryLost),
+        Err(err) => return Err(ApiError::mongo(err)),
+    };
+    tracing::info!("watching project changes");
+
+    while let Some(event) = stream.next().await {
+        let event = match event {
+            Ok(event) => event,
+            Err(err) if history_lost(&err) => return Ok(Stop::HistoryLost),
+            Err(err) => return Err(ApiError::mongo(err)),
+        };
+
+        if event.operation_type == OperationType::Invalidate {
+            // An invalidated stream cannot be resumed; start over from the current time.
+            *resume_token = None;
+            return Ok(Stop::Closed);
+        }
+
+        let Some(project_id) = event
+            .document_key
+            .as_ref()
+            .and_then(|key| key.get_str("_id").ok())
+        else {
+            continue;
+        };
+
+        if event.operation_type == OperationType::Delete {
+            delete_project_structures(state, project_id)
+                .await
+                .inspect_err(|err| {
+                    tracing::error!(project_id, error = %err, "failed to delete project structures")
+                })?;
+        } else if event.operation_type == OperationType::Update
+            && event
+                .update_description
+                .as_ref()
+                .is_some_and(only_touches_cover_images)
+        {
+            tracing::debug!(project_id, "skipping a cover image update");
+        } else if let Err(failure) = reindex_project(state, project_id).await {
+            record_failure(state, project_id, None, &failure).await;
+            if failure.is_transient() {
+                if retries.retry(project_id) {
+                    return Err(failure.into());
+                }
+                hand_to_sweep(state, project_id).await;
+            }
+        }
+        *retries = EventRetries::default();
+
+        if let Some(token) = stream.resume_token() {
+            if let Some(tokens) = &state.change_stream_token_repository {
+                tokens.save(RESUME_TOKEN_NAME, &token).await?;
+            }
+            *resume_token = Some(token);
+        }
+    }
+
+    Ok(Stop::Closed)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,60 @@
+use bson::{Document, doc};
+use mongodb::change_stream::event::UpdateDescription;
+
+use super::{EventRetries, MAX_EVENT_ATTEMPTS, only_touches_cover_images};
+
+fn update(description: Document) -> UpdateDescription {
+    bson::from_document(description).expect("valid update description")
+}
+
+#[test]
+fn skips_updates_that_only_set_cover_images() {
+    let persisted = update(doc! {
+        "updatedFields": { "defaultCoverImage": "https://cdn.example.com/a.jpg" },
+        "removedFields": [],
+    });
+    assert!(only_touches_cover_images(&persisted));
+
+    let covers = update(doc! {
+        "updatedFields": { "coverImage": "a.jpg", "coverRenderImageId": "render-1" },
+        "removedFields": ["defaultCoverImage"],
+    });
+    assert!(only_touches_cover_images(&covers));
+}
+
+#[test]
+fn keeps_updates_that_touch_anything_else() {
+    let edited = update(doc! {
+        "updatedFields": {
+            "defaultCoverImage": "https://cdn.example.com/a.jpg",
+            "updatedAt": bson::DateTime::now(),
+        },
+        "removedFields": [],
+    });
+    assert!(!only_touches_cover_images(&edited));
+
+    let removed = update(doc! {
+        "updatedFields": { "defaultCoverImage": "https://cdn.example.com/a.jpg" },
+        "removedFields": ["floorplanPath"],
+    });
+    assert!(!only_touches_cover_images(&removed));
+
+    // A prefix of a cover image field name is a different field.
+    let lookalike = update(doc! {
+        "updatedFields": { "coverImages.0": "a.jpg" },
+        "removedFields": [],
+    });
+    assert!(!only_touches_cover_images(&lookalike));
+}
+
+#[test]
+fn retries_an_event_a_bounded_number_of_times() {
+    let mut retries = EventRetries::default();
+    for _ in 1..MAX_EVENT_ATTEMPTS {
+        assert!(retries.retry("project-a"));
+    }
+    assert!(!retries.retry("project-a"));
+
+    // Another project's event starts its own count.
+    assert!(retries.retry("project-b"));
+}
@@ -0,0 +1,369 @@
+use std::{collections::HashMap, sync::OnceLock, time::Duration};
+
+use anyhow::anyhow;
+use futures_util::{StreamExt, stream};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    error::{ApiError, IndexFailure},
+    handlers::project_handler::reindex_project,
+    models::job::{self, JobFailure, JobKind},
+    repositories::index_watermark_repository::IndexWatermark,
+    routes::project::dto::StructureIndexResponse,
+    state::AppState,
+};
+
+/// Projects a [`JobKind::RecentIndex`] job rebuilds.
+pub const RECENT_PROJECT_LIMIT: i64 = 300;
+/// Watermark row used by [`JobKind::IncrementalIndex`] jobs.
+const PROJECT_WATERMARK: &str = "projects";
+const INDEX_PAGE_SIZE: i64 = 100;
+/// Incremental jobs in a row a transiently failing project may hold the watermark for.
+const MAX_TRANSIENT_ATTEMPTS: u32 = 5;
+
+/// How long a claimed job stays with its instance without a renewal.
+const JOB_LEASE: Duration = Duration::from_secs(60);
+/// Leases are renewed well before they run out.
+const LEASE_RENEWAL: Duration = Duration::from_secs(20);
+
+/// Job that catches up on changed projects: incremental with a watermark store, otherwise a
+/// rebuild of the most recent projects.
+pub fn changed_projects_job(state: &AppState) -> JobKind {
+    if state.index_watermark_repository.is_some() {
+        JobKind::IncrementalIndex
+    } else {
+        JobKind::RecentIndex
+    }
+}
+
+/// Record a queued job and start processing it in the background.
+///
+/// Incremental jobs share one watermark, so while one is queued or running it is returned
+/// instead of starting another. An active full reindex likewise already covers a new request,
+/// and a narrower job requested meanwhile is a conflict: it would only rebuild the same
+/// projects a second time.
+pub async fn enqueue(state: &AppState, kind: JobKind) -> Result<job::Model, ApiError> {
+    let jobs = state.job_repository();
+    if matches!(kind, JobKind::IncrementalIndex | JobKind::FullReindex)
+        && let Some(active) = jobs.find_active(kind).await?
+    {
+        return Ok(active);
+    }
+    if kind != JobKind::FullReindex
+        && let Some(full) = jobs.find_active(JobKind::FullReindex).await?
+    {
+        return Err(
+            ApiError::conflict("a full reindex is already queued or running")
+                .with_details(json!({ "jobId": full.id })),
+        );
+    }
+
+    let job = job::Model::new(kind);
+    if !jobs.insert(&job).await? {
+        // Another request or instance queued one since the lookup above.
+        return jobs
+            .find_active(kind)
+            .await?
+            .ok_or_else(|| ApiError::internal(anyhow!("active {kind:?} job disappeared")));
+    }
+
+    let job = match &state.job_repository {
+        Some(repository) => match repository.claim(job.id, instance_id(), JOB_LEASE).await? {
+            Some(job) => job,
+            // Picked up by another instance's lease sweep; it runs there.
+            None => return Ok(job),
+        },
+        None => job,
+    };
+
+    tokio::spawn(run(state.clone(), job.clone()));
+    Ok(job)
+}
+
+/// Claim jobs that are queued or whose instance stopped renewing their lease, and run them.
+pub async fn resume_unfinished(state: &AppState) -> Result<usize, ApiError> {
+    let Some(repository) = &state.job_repository else {
+        return Ok(0);
+    };
+
+    let jobs = repository
+        .claim_unfinished(instance_id(), JOB_LEASE)
+        .await?;
+    let count = jobs.len();
+    for job in jobs {
+        tokio::spawn(run(state.clone(), job));
+    }
+
+    Ok(count)
+}
+
+/// Periodically take over jobs left behind by instances that stopped.
+pub async fn reclaim_expired(state: AppState) {
+    let mut interval = tokio::time::interval(JOB_LEASE);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match resume_unfinished(&state).await {
+            Ok(0) => {}
+            Ok(resumed) => tracing::info!(resumed, "took over abandoned indexing jobs"),
+            Err(err) => tracing::warn!(error = %err, "failed to claim abandoned indexing jobs"),
+        }
+    }
+}
+
+/// Identifies this process as the owner of the jobs it claims.
+fn instance_id() -> &'static str {
+    static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+    INSTANCE_ID.get_or_init(|| {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+        format!("{host}-{}", Uuid::new_v4())
+    })
+}
+
+async fn run(state: AppState, mut job: job::Model) {
+    let job_id = job.id;
+    job.start();
+
+    let heartbeat = state.job_repository.clone().map(|repository| {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(LEASE_RENEWAL);
+            loop {
+                interval.tick().await;
+                match repository
+                    .renew_lease(job_id, instance_id(), JOB_LEASE)
+                    .await
+                {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        tracing::warn!(%job_id, "lost the lease of an indexing job");
+                        break;
+                    }
+                    Err(err) => tracing::warn!(%job_id, error = %err, "failed to renew job lease"),
+                }
+            }
+        })
+    });
+
+    let mut progress = Progress::default();
+    let result = match state.job_repository().save(&job).await {
+        Ok(()) => match job.kind {
+            JobKind::IncrementalIndex => index_changed(&state, &mut job, &mut progress).await,
+            JobKind::RecentIndex => index_recent(&state, &mut job, &mut progress).await,
+            JobKind::FullReindex => index_all(&state, &mut job, &mut progress).await,
+        },
+        Err(err) => Err(err),
+    };
+
+    if let Err(err) = &result {
+        tracing::error!(%job_id, error = %err, "indexing job failed");
+    }
+    progress.write_to(&mut job);
+    job.finish(result.err().map(|err| err.to_string()));
+
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.abort();
+    }
+    match state.job_repository().save(&job).await {
+        Ok(()) => tracing::info!(
+            %job_id,
+            processed = job.processed,
+            failed = progress.failures.len(),
+            "indexing job finished"
+        ),
+        Err(err) => tracing::error!(%job_id, error = %err, "failed to record job result"),
+    }
+}
+
+async fn index_recent(
+    state: &AppState,
+    job: &mut job::Model,
+    progress: &mut Progress,
+) -> Result<(), ApiError> {
+    let ids = state
+        .project_repository()?
+        .find_recent_ids(RECENT_PROJECT_LIMIT)
+        .await?;
+    index_batch(state, job, progress, ids).await?;
+    Ok(())
+}
+
+/// Page through every project, oldest change first. The incremental watermark is left alone.
+async fn index_all(
+    state: &AppState,
+    job: &mut job::Model,
+    progress: &mut Progress,
+) -> Result<(), ApiError> {
+    let project_repository = state.project_repository()?;
+
+    let mut after = None;
+    loop {
+        let page = project_repository
+            .find_changed_since(after.as_ref(), INDEX_PAGE_SIZE)
+            .await?;
+        let Some(last) = page.last().cloned() else {
+            break;
+        };
+
+        let ids = page
+            .into_iter()
+            .map(|watermark| watermark.project_id)
+            .collect();
+        index_batch(state, job, progress, ids).await?;
+        after = Some(last);
+    }
+
+    Ok(())
+}
+
+/// Page through projects changed since the watermark, advancing it after each page.
+///
+/// The watermark never moves past a project that failed transiently: it is held just before
+/// the first such project and the job stops, so the next incremental job retries from there.
+/// A project still failing after [`MAX_TRANSIENT_ATTEMPTS`] jobs is given up on and passed.
+async fn index_changed(
+    state: &AppState,
+    job: &mut job::Model,
+    progress: &mut Progress,
+) -> Result<(), ApiError> {
+    let project_repository = state.project_repository()?;
+    let watermarks = state.index_watermarks();
+
+    let mut watermark = watermarks.find(PROJECT_WATERMARK).await?;
+    loop {
+        let page = project_repository
+            .find_changed_since(watermark.position.as_ref(), INDEX_PAGE_SIZE)
+            .await?;
+        let Some(last) = page.last().cloned() else {
+            break;
+        };
+
+        let ids = page
+            .iter()
+            .map(|watermark| watermark.project_id.clone())
+            .collect();
+        let retry = index_batch(state, job, progress, ids).await?;
+
+        let mut held = None;
+        for (position, entry) in page.iter().enumerate() {
+            let Some(failure) = retry.get(&entry.project_id) else {
+                continue;
+            };
+            let attempts = match &watermark.held_project_id {
+                Some(held) if *held == entry.project_id => watermark.held_attempts + 1,
+                _ => 1,
+            };
+            if attempts < MAX_TRANSIENT_ATTEMPTS {
+                held = Some((position, attempts));
+                break;
+            }
+
+            let failure = IndexFailure::new(
+                failure.reason,
+                format!("gave up after {attempts} attempts: {}", failure.message),
+            );
+            record_failure(state, &entry.project_id, Some(job.id), &failure).await;
+        }
+
+        if let Some((position, attempts)) = held {
+            let previous = position.checked_sub(1).map(|index| page[index].clone());
+            let watermark = IndexWatermark {
+                position: previous.or(watermark.position),
+                held_project_id: Some(page[position].project_id.clone()),
+                held_attempts: attempts,
+            };
+            watermarks.save(PROJECT_WATERMARK, &watermark).await?;
+            tracing::warn!(
+                job_id = %job.id,
+                project_id = page[position].project_id,
+                attempts,
+                "holding the watermark at a project that failed transiently"
+            );
+            break;
+        }
+
+        watermark = IndexWatermark {
+            position: Some(last),
+            ..IndexWatermark::default()
+        };
+        watermarks.save(PROJECT_WATERMARK, &watermark).await?;
+    }
+
+    Ok(())
+}
+
+/// Rebuild `ids` with bounded concurrency, saving progress after every project.
+///
+/// Returns the failures that may not recur on a later attempt, by project.
+async fn index_batch(
+    state: &AppState,
+    job: &mut job::Model,
+    progress: &mut Progress,
+    ids: Vec<String>,
+) -> Result<HashMap<String, IndexFailure>, ApiError> {
+    job.total += ids.len() as i32;
+
+    let mut results = stream::iter(ids)
+        .map(|project_id| async move {
+            let result = reindex_project(state, &project_id).await;
+            (project_id, result)
+        })
+        .buffer_unordered(state.index_concurrency.max(1));
+
+    let mut retry = HashMap::new();
+    while let Some((project_id, result)) = results.next().await {
+        match result {
+            Ok(response) => progress.summary += response,
+            Err(failure) => {
+                record_failure(state, &project_id, Some(job.id), &failure).await;
+                progress.failures.push(JobFailure {
+                    project_id: project_id.clone(),
+                    reason: failure.reason,
+                    error: failure.message.clone(),
+                });
+                if failure.is_transient() {
+                    retry.insert(project_id, failure);
+                }
+            }
+        }
+
+        job.processed += 1;
+        progress.write_to(job);
+        state.job_repository().save(job).await?;
+    }
+
+    Ok(retry)
+}
+
+/// Log a project that could not be indexed and keep it in `structure_index_errors` for triage.
+pub async fn record_failure(
+    state: &AppState,
+    project_id: &str,
+    job_id: Option<Uuid>,
+    failure: &IndexFailure,
+) {
+    tracing::warn!(project_id, reason = ?failure.reason, error = %failure, "failed to index project");
+
+    let Some(repository) = &state.structure_index_error_repository else {
+        return;
+    };
+    if let Err(err) = repository.insert(project_id, job_id, failure).await {
+        tracing::error!(project_id, error = %err, "failed to record index failure");
+    }
+}
+
+#[derive(Default)]
+struct Progress {
+    summary: StructureIndexResponse,
+    failures: Vec<JobFailure>,
+}
+
+impl Progress {
+    fn write_to(&self, job: &mut job::Model) {
+        job.summary = serde_json::to_value(&self.summary).unwrap_or_default();
+        job.failures = serde_json::to_value(&self.failures).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,162 @@
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+
+use super::{MAX_TRANSIENT_ATTEMPTS, PROJECT_WATERMARK, Progress, index_changed};
+use crate::{
+    models::{
+        job::{self, JobKind},
+        project::Project,
+    },
+    repositories::{
+        dependency_probe::DependencyProbe,
+        fixture_loader::load_fixtures,
+        floorplan_fetcher::{FetchOptions, FloorplanFetcher},
+        object_store::{
+            GetOutcome, LocalObjectStore, ObjectMeta, ObjectStore, ObjectStoreError, Validators,
+        },
+        structure_index::StructureIndex,
+    },
+    state::AppState,
+};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+/// The fixture store, except that one project's floorplans are never reachable.
+struct UnreachableProject {
+    inner: LocalObjectStore,
+    key: String,
+}
+
+#[async_trait]
+impl ObjectStore for UnreachableProject {
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectMeta>, ObjectStoreError> {
+        self.inner.list(prefix).await
+    }
+
+    async fn get(
+        &self,
+        key: &str,
+        validators: Option<&Validators>,
+    ) -> Result<GetOutcome, ObjectStoreError> {
+        if key == self.key {
+            return Err(ObjectStoreError::Unavailable(
+                "503 Service Unavailable".to_string(),
+            ));
+        }
+        self.inner.get(key, validators).await
+    }
+
+    async fn ping(&self) -> Result<(), ObjectStoreError> {
+        self.inner.ping().await
+    }
+}
+
+/// Fixture state whose floorplan fetches for `project_id` always fail transiently.
+fn state_failing(project_id: &str) -> AppState {
+    let root = Path::new(FIXTURES);
+    let fixtures = load_fixtures(root).expect("fixtures load");
+    let object_store: Arc<dyn ObjectStore> = Arc::new(UnreachableProject {
+        inner: LocalObjectStore::new(root.to_path_buf()),
+        key: Project::floorplan_key(project_id),
+    });
+    let index = Arc::new(StructureIndex::open(None).expect("empty index"));
+    let fetch = FetchOptions {
+        max_retries: 0,
+        ..FetchOptions::default()
+    };
+
+    AppState {
+        project_repository: Some(Arc::new(fixtures.projects)),
+        image_repository: Some(Arc::new(fixtures.images)),
+        floor_structure_repository: index.clone(),
+        room_structure_repository: index,
+        index_watermark_repository: None,
+        change_stream_token_repository: None,
+        job_repository: None,
+        structure_index_error_repository: None,
+        in_memory_jobs: Arc::default(),
+        in_memory_watermarks: Arc::default(),
+        image_store: object_store.clone(),
+        cdn_base_url: "https://cdn.example.com".to_string(),
+        floorplan_fetcher: FloorplanFetcher::new(object_store.clone(), fetch),
+        dependency_probe: DependencyProbe::new(object_store),
+        scoring_profiles: Arc::default(),
+        index_concurrency: 1,
+    }
+}
+
+/// Run one incremental job to completion and return it.
+async fn run_incremental(state: &AppState) -> job::Model {
+    let mut job = job::Model::new(JobKind::IncrementalIndex);
+    let mut progress = Progress::default();
+    index_changed(state, &mut job, &mut progress)
+        .await
+        .expect("job runs");
+    progress.write_to(&mut job);
+    job
+}
+
+#[tokio::test]
+async fn gives_up_on_a_project_that_keeps_failing_transiently() {
+    // project-a changed first; the healthy project-b to project-d follow it.
+    let state = state_failing("project-a");
+    let watermarks = state.index_watermarks();
+
+    for attempt in 1..MAX_TRANSIENT_ATTEMPTS {
+        let job = run_incremental(&state).await;
+        assert_eq!(job.total, 4, "attempt {attempt} reindexes the whole page");
+        assert_eq!(job.failures[0]["projectId"], "project-a");
+        assert!(
+            job.summary["indexedProjects"].as_u64() > Some(0),
+            "projects after the held one are indexed"
+        );
+
+        let watermark = watermarks.find(PROJECT_WATERMARK).await.expect("readable");
+        assert_eq!(watermark.position, None);
+        assert_eq!(watermark.held_project_id.as_deref(), Some("project-a"));
+        assert_eq!(watermark.held_attempts, attempt);
+    }
+
+    let job = run_incremental(&state).await;
+    assert_eq!(job.total, 4);
+    let watermark = watermarks.find(PROJECT_WATERMARK).await.expect("readable");
+    assert_eq!(
+        watermark
+            .position
+            .as_ref()
+            .map(|position| position.project_id.as_str()),
+        Some("project-d")
+    );
+    assert_eq!(watermark.held_project_id, None);
+    assert_eq!(watermark.held_attempts, 0);
+
+    let job = run_incremental(&state).await;
+    assert_eq!(
+        job.total, 0,
+        "nothing is left once the project is given up on"
+    );
+}
+
+#[tokio::test]
+async fn a_project_failing_again_after_a_healthy_one_starts_its_own_count() {
+    let state = state_failing("project-c");
+    let watermarks = state.index_watermarks();
+
+    run_incremental(&state).await;
+    let watermark = watermarks.find(PROJECT_WATERMARK).await.expect("readable");
+    assert_eq!(
+        watermark
+            .position
+            .as_ref()
+            .map(|position| position.project_id.as_str()),
+        Some("project-b")
+    );
+    assert_eq!(watermark.held_project_id.as_deref(), Some("project-c"));
+    assert_eq!(watermark.held_attempts, 1);
+
+    let job = run_incremental(&state).await;
+    assert_eq!(job.total, 2, "the retry starts at the held project");
+    let watermark = watermarks.find(PROJECT_WATERMARK).await.expect("readable");
+    assert_eq!(watermark.held_attempts, 2);
+}